@@ -0,0 +1,180 @@
+extern crate alloc;
+
+use alloc::collections::BTreeSet;
+use crate::compress::Encoding;
+use core::hash::{Hash as _, Hasher as _};
+use globset::{Error as GlobError, Glob, GlobMatcher};
+use http::header::{HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH};
+use httpdate::HttpDate;
+use std::hash::DefaultHasher;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs::metadata;
+
+
+/// `Cache-Control` values for responses, picked by the path of
+/// the file served, relative to root.
+#[derive(Debug, Clone, Default)]
+pub struct CacheControl {
+    /// The value for paths no rule matches, if any.
+    fallback: Option<String>,
+
+    /// Globs and the values for paths they match, first match wins.
+    rules: Vec<(GlobMatcher, String)>
+}
+
+impl CacheControl {
+    /// The `Cache-Control` value for a path relative to root, if any.
+    #[inline]
+    #[must_use]
+    pub fn for_path(&self, path: &Path) -> Option<&str> {
+        self.rules.iter()
+            .find(|rule| rule.0.is_match(path))
+            .map(|rule| rule.1.as_str())
+            .or(self.fallback.as_deref())
+    }
+
+    /// Create cache control from glob and value pairs, like
+    /// `("**/*.html", "no-cache")`. The first glob matching a
+    /// path picks its value.
+    ///
+    /// # Errors
+    ///
+    /// Errors if a glob is invalid.
+    #[inline]
+    pub fn new<I: IntoIterator<Item = (String, String)>>(rules: I) -> Result<Self, GlobError> {
+        let compiled = rules.into_iter()
+            .map(|(glob, value)| Ok((Glob::new(&glob)?.compile_matcher(), value)))
+            .collect::<Result<Vec<_>, GlobError>>()?;
+
+        Ok(Self {
+            fallback: None,
+            rules: compiled
+        })
+    }
+
+    /// Set the value for paths no rule matches.
+    #[inline]
+    #[must_use]
+    pub fn with_fallback(mut self, value: String) -> Self {
+        self.fallback = Some(value);
+        self
+    }
+}
+
+/// What a client can revalidate a response with.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Validators {
+    /// The entity tag, quoted.
+    pub etag: String,
+
+    /// When the response last changed, if known.
+    pub last_modified: Option<SystemTime>
+}
+
+impl Validators {
+    /// Create validators for a file served as it is, from its
+    /// size and modification time, so it doesn't need to be read.
+    #[inline]
+    #[must_use]
+    pub fn for_file(len: u64, modified: Option<SystemTime>, encoding: Option<Encoding>) -> Self {
+        let secs = modified
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_secs());
+
+        let etag = encoding.map_or_else(
+            || format!("\"{len:x}-{secs:x}\""),
+            |enc| format!("\"{len:x}-{secs:x}-{}\"", enc.name())
+        );
+
+        Self {
+            etag,
+            last_modified: modified
+        }
+    }
+
+    /// Whether a request's conditional headers say the client
+    /// already has this response. `If-None-Match` wins over
+    /// `If-Modified-Since` when both are sent.
+    #[inline]
+    #[must_use]
+    pub fn is_not_modified(&self, headers: &HeaderMap) -> bool {
+        if let Some(if_none_match) = headers.get(IF_NONE_MATCH) {
+            return if_none_match.to_str().is_ok_and(|tags| {
+                tags.split(',')
+                    .map(str::trim)
+                    .any(|tag| tag == "*" || tag.trim_start_matches("W/") == self.etag)
+            });
+        }
+
+        let since = headers.get(IF_MODIFIED_SINCE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<HttpDate>().ok());
+
+        match (since, self.last_modified) {
+            // HTTP dates only go down to the second
+            (Some(since_date), Some(modified)) => HttpDate::from(modified) <= since_date,
+            _ => false
+        }
+    }
+
+    /// The `Last-Modified` header value, if the time is known.
+    #[inline]
+    #[must_use]
+    pub fn last_modified_header(&self) -> Option<String> {
+        self.last_modified.map(httpdate::fmt_http_date)
+    }
+
+    /// Whether an `If-Range` value still describes this response,
+    /// so a range of it can be sent. Weak tags never match.
+    #[inline]
+    #[must_use]
+    pub fn matches_if_range(&self, value: &str) -> bool {
+        let trimmed = value.trim();
+
+        if trimmed.starts_with('"') || trimmed.starts_with("W/") {
+            return trimmed == self.etag;
+        }
+
+        match (trimmed.parse::<HttpDate>(), self.last_modified) {
+            (Ok(date), Some(modified)) => HttpDate::from(modified) == date,
+            _ => false
+        }
+    }
+
+    /// Create validators for a body. `body` is the uncompressed
+    /// body, and `encoding` what it's sent compressed with, which
+    /// gets its own tag.
+    #[inline]
+    #[must_use]
+    pub fn new(body: &[u8], encoding: Option<Encoding>, last_modified: Option<SystemTime>) -> Self {
+        let mut hasher = DefaultHasher::new();
+        body.hash(&mut hasher);
+
+        let hash = hasher.finish();
+        let etag = encoding.map_or_else(
+            || format!("\"{hash:016x}\""),
+            |enc| format!("\"{hash:016x}-{}\"", enc.name())
+        );
+
+        Self {
+            etag,
+            last_modified
+        }
+    }
+}
+
+/// The latest modification time of a set of files, if every one
+/// of them has one.
+#[inline]
+pub async fn last_modified(paths: &BTreeSet<PathBuf>) -> Option<SystemTime> {
+    let mut latest = None;
+
+    for path in paths {
+        let modified = metadata(path).await.ok()?.modified().ok()?;
+        latest = latest.max(Some(modified));
+    }
+
+    latest
+}
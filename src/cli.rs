@@ -0,0 +1,32 @@
+/// Caching what each output was built from, for incremental builds.
+pub mod cache;
+
+/// Removing outputs of previous builds.
+pub mod clean;
+
+/// Checking a site for problems without building it.
+pub mod check;
+
+/// Configuration, read from reqaz.json and CLI options.
+pub mod config;
+
+/// Comparing a build to what's in the output directory.
+pub mod diff;
+
+/// Giving assets content-hashed names.
+pub mod fingerprint;
+
+/// Building pipelines into the output directory.
+pub mod generate;
+
+/// Recording what a build produced.
+pub mod manifest;
+
+/// Serving the output directory as it would be deployed.
+pub mod preview;
+
+/// Reporting the outcome of a build.
+pub mod report;
+
+/// Serving root with the dev server.
+pub mod serve;
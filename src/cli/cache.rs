@@ -0,0 +1,159 @@
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use color_eyre::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use std::collections::HashSet;
+use std::fs::{create_dir_all, read, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+
+/// Bumped whenever the cache format changes, so older caches are
+/// ignored rather than misread.
+const CACHE_VERSION: u32 = 4;
+
+/// The file the build graph is kept in, inside the cache directory.
+const GRAPH_FILE: &str = "graph.json";
+
+/// What every output was built from on previous runs.
+#[derive(Serialize, Deserialize, Default)]
+#[expect(clippy::module_name_repetitions, reason = "`BuildCache` tells it apart from the library's HTTP caching")]
+pub struct BuildCache {
+    /// Cached outputs, keyed by their path relative to the output directory.
+    outputs: BTreeMap<PathBuf, CachedOutput>,
+
+    /// The cache format version.
+    version: u32
+}
+
+/// What a single output was built from.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CachedOutput {
+    /// Content hashes of every file read to build the output.
+    pub dependencies: BTreeMap<PathBuf, String>,
+
+    /// The pipeline input URI or asset path.
+    pub input: String,
+
+    /// The MIME type of the output.
+    pub mime: String,
+
+    /// The mods and compression settings the output was built with.
+    pub mods: String,
+
+    /// Local paths the output references, as URI paths, so it's
+    /// rebuilt when one of them gets a new fingerprint.
+    pub references: BTreeSet<String>,
+
+    /// Every URL fetched over the network. If there are any,
+    /// the output is always rebuilt.
+    pub remote: BTreeSet<String>
+}
+
+impl BuildCache {
+    /// The cache directory for an output directory. It sits next
+    /// to the output directory and is hidden, so it's never picked
+    /// up as an asset.
+    pub fn dir_for(output_dir: &Path) -> PathBuf {
+        let name = output_dir.file_name()
+            .map_or_else(|| "build".to_owned(), |name| name.to_string_lossy().into_owned());
+
+        output_dir.with_file_name(format!(".{name}.cache"))
+    }
+
+    /// What an output was last built from.
+    pub fn get(&self, output: &Path) -> Option<&CachedOutput> {
+        self.outputs.get(output)
+    }
+
+    /// Record what an output was built from.
+    pub fn insert(&mut self, output: PathBuf, cached: CachedOutput) {
+        self.outputs.insert(output, cached);
+    }
+
+    /// Whether an output is up to date: it was last built from the
+    /// same input with the same mods, still exists, fetched nothing
+    /// remote, and none of the files it was built from have changed since.
+    pub fn is_fresh(&self, input: &str, output: &Path, output_dir: &Path, mods: &str) -> bool {
+        let Some(cached) = self.outputs.get(output) else {
+            return false;
+        };
+
+        cached.input == input
+            && cached.mods == mods
+            && cached.remote.is_empty()
+            && output_dir.join(output).is_file()
+            && cached.dependencies.iter().all(|(path, hash)| content_hash(path) == *hash)
+    }
+
+    /// Load the cache from a cache directory. A missing, unreadable
+    /// or outdated cache is treated as empty, so everything gets built.
+    #[expect(clippy::single_call_fn, reason = "it's the counterpart of `save`")]
+    pub fn load(cache_dir: &Path) -> Self {
+        read_to_string(cache_dir.join(GRAPH_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str::<Self>(&json).ok())
+            .filter(|cache| cache.version == CACHE_VERSION)
+            .unwrap_or_default()
+    }
+
+    /// Forget an output, so it's built next time.
+    pub fn remove(&mut self, output: &Path) {
+        self.outputs.remove(output);
+    }
+
+    /// Forget every output not in `outputs`.
+    pub fn retain(&mut self, outputs: &HashSet<&Path>) {
+        self.outputs.retain(|output, _| outputs.contains(output.as_path()));
+    }
+
+    /// Write the cache into a cache directory.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the cache directory can't be written to.
+    pub fn save(&mut self, cache_dir: &Path) -> Result<()> {
+        self.version = CACHE_VERSION;
+
+        create_dir_all(cache_dir)?;
+        write(cache_dir.join(GRAPH_FILE), serde_json::to_vec_pretty(self)?)?;
+
+        Ok(())
+    }
+}
+
+impl CachedOutput {
+    /// Hash the files an output was built from.
+    pub fn new(
+        input: String,
+        mods: String,
+        mime: String,
+        dependencies: &BTreeSet<PathBuf>,
+        remote: BTreeSet<String>
+    ) -> Self {
+        Self {
+            dependencies: dependencies.iter()
+                .map(|path| (path.clone(), content_hash(path)))
+                .collect(),
+            input,
+            mime,
+            mods,
+            references: BTreeSet::new(),
+            remote
+        }
+    }
+}
+
+/// The hex SHA-256 of a file's contents. Missing or unreadable
+/// files hash to an empty string, so creating them counts as a change.
+pub fn content_hash(path: &Path) -> String {
+    read(path)
+        .map(|contents| hex_digest(&contents))
+        .unwrap_or_default()
+}
+
+/// The hex SHA-256 of some bytes.
+pub fn hex_digest(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
@@ -0,0 +1,289 @@
+#![expect(clippy::module_name_repetitions, reason = "`CheckFormat` and `CheckReport` read better than a bare `Format` and `Report`")]
+
+use super::config::{GenerateConfig, PipelineConfig};
+use super::generate::{collect_jobs, BuildJob};
+use clap::ValueEnum;
+use color_eyre::owo_colors::OwoColorize as _;
+use color_eyre::Result;
+use eyre::Report;
+use html5ever::local_name;
+use http::uri::Uri;
+use kuchikiki::traits::TendrilSink as _;
+use reqaz::html::attr::{GetAttr as _, Href};
+use reqaz::mediatype::TEXT_HTML;
+use reqaz::source::SourceResolver;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::ExitCode;
+use tokio::task::spawn_blocking;
+
+
+/// How to print a check report.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CheckFormat {
+    /// Readable text.
+    Human,
+
+    /// A single JSON object, for other tools.
+    Json
+}
+
+/// Everything a check found.
+#[derive(Serialize)]
+pub struct CheckReport {
+    /// How many inputs were resolved.
+    pub checked: usize,
+
+    /// Every problem found, in pipeline order.
+    pub problems: Vec<Problem>
+}
+
+/// A problem found while checking an input.
+#[derive(Serialize)]
+pub struct Problem {
+    /// The pipeline input URI or asset path.
+    pub input: String,
+
+    /// What sort of problem this is.
+    pub kind: ProblemKind,
+
+    /// What went wrong.
+    pub message: String
+}
+
+/// The sort of problem a check found.
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProblemKind {
+    /// An internal link points to a file that doesn't exist.
+    BrokenLink,
+
+    /// A `nib-mod="fetch"` element couldn't be fetched.
+    Fetch,
+
+    /// The input failed to resolve, like a component that
+    /// failed to load, CSS that couldn't be parsed or SCSS
+    /// that didn't compile.
+    Resolve
+}
+
+impl CheckReport {
+    /// The process exit code for the check, non-zero if anything was found.
+    pub const fn exit_code(&self) -> ExitCode {
+        if self.problems.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        }
+    }
+
+    /// Print the report in the given format.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the report can't be serialized.
+    #[expect(clippy::print_stderr, reason = "problems are reported like errors")]
+    #[expect(clippy::print_stdout, reason = "the summary and JSON report are the command's output")]
+    pub fn print(&self, format: CheckFormat) -> Result<()> {
+        match format {
+            CheckFormat::Json => println!("{}", serde_json::to_string_pretty(self)?),
+            CheckFormat::Human => {
+                for problem in &self.problems {
+                    eprintln!(
+                        "{} {} ({})",
+                        "error:".red().bold(),
+                        problem.input,
+                        problem.kind.name()
+                    );
+
+                    for line in problem.message.lines() {
+                        eprintln!("    {line}");
+                    }
+                }
+
+                if self.problems.is_empty() {
+                    println!("Checked {} inputs, no problems found.", self.checked);
+                } else {
+                    println!(
+                        "Checked {} inputs, {} problems found.",
+                        self.checked,
+                        self.problems.len().red().bold()
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ProblemKind {
+    /// The name of the problem kind, as it appears in JSON reports.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Resolve => "resolve",
+            Self::Fetch => "fetch",
+            Self::BrokenLink => "broken-link"
+        }
+    }
+}
+
+/// Resolve every pipeline and SCSS asset without writing anything,
+/// and report any problems. Without a generate config, every page
+/// under root is checked.
+#[expect(clippy::single_call_fn, reason = "the `check` subcommand's entry point")]
+pub async fn run(
+    resolver: &SourceResolver,
+    generate: Option<&GenerateConfig>,
+    format: CheckFormat
+) -> Result<ExitCode> {
+    let jobs = match generate {
+        Some(generate_config) => collect_jobs(resolver, generate_config)?,
+        None => resolver.discover_pages()?
+            .into_iter()
+            .map(|page| BuildJob::Pipeline(PipelineConfig {
+                input: page.uri,
+                output: page.path,
+                mods: None
+            }))
+            .collect()
+    };
+
+    let check_resolver = resolver.clone();
+    let report = spawn_blocking(move || check_jobs(&check_resolver, &jobs)).await?;
+
+    report.print(format)?;
+
+    Ok(report.exit_code())
+}
+
+/// Resolve every job that isn't a plain copy, collecting problems.
+#[expect(clippy::single_call_fn, reason = "runs on a blocking thread, apart from `run`")]
+fn check_jobs(resolver: &SourceResolver, jobs: &[BuildJob]) -> CheckReport {
+    let outputs: HashSet<String> = jobs.iter()
+        .flat_map(|job| output_uri_paths(job.output()))
+        .collect();
+
+    let mut report = CheckReport {
+        checked: 0,
+        problems: vec![]
+    };
+
+    for job in jobs {
+        let Some(uri) = job.source_uri() else {
+            continue;
+        };
+
+        report.checked = report.checked.saturating_add(1);
+
+        let source = match job.resolver_for(resolver).resolve_source(uri) {
+            Ok(found) => found,
+            Err(err) => {
+                report.problems.push(Problem {
+                    input: job.input(),
+                    kind: ProblemKind::Resolve,
+                    message: format!("{:#}", Report::new(err))
+                });
+
+                continue;
+            }
+        };
+
+        for warning in source.warnings {
+            report.problems.push(Problem {
+                input: job.input(),
+                kind: ProblemKind::Fetch,
+                message: warning
+            });
+        }
+
+        if source.mime == TEXT_HTML {
+            for link in broken_links(resolver, uri, &source.body, &outputs) {
+                report.problems.push(Problem {
+                    input: job.input(),
+                    kind: ProblemKind::BrokenLink,
+                    message: format!("Link to `{link}` doesn't exist")
+                });
+            }
+        }
+    }
+
+    report
+}
+
+/// Find every `<a href>` and `<img src>` in a page pointing to a
+/// file that isn't under root or built by a pipeline.
+#[expect(clippy::single_call_fn, reason = "link checking is a step of its own for pages")]
+fn broken_links(
+    resolver: &SourceResolver,
+    page_uri: &Uri,
+    body: &[u8],
+    outputs: &HashSet<String>
+) -> Vec<String> {
+    let html = kuchikiki::parse_html().one(String::from_utf8_lossy(body).into_owned());
+
+    let Ok(links) = html.select("a[href], img[src]") else {
+        return vec![];
+    };
+
+    links
+        .filter_map(|link| {
+            let attr_name = if link.name.local == local_name!("img") {
+                "src"
+            } else {
+                "href"
+            };
+
+            link.get_attr(attr_name)
+        })
+        .filter(|target| !link_exists(resolver, page_uri, target, outputs))
+        .collect()
+}
+
+/// Whether a link target exists. Links to other sites, other
+/// schemes and the page itself always do.
+#[expect(clippy::single_call_fn, reason = "keeps the filter in `broken_links` readable")]
+fn link_exists(
+    resolver: &SourceResolver,
+    page_uri: &Uri,
+    target: &str,
+    outputs: &HashSet<String>
+) -> bool {
+    // Fragments and queries don't change which file is linked
+    let target_path = target.split(['#', '?']).next().unwrap_or_default();
+
+    if target_path.is_empty() {
+        return true;
+    }
+
+    let Ok(href @ (Href::Absolute(_) | Href::Relative(_))) = Href::try_from(target_path) else {
+        return true;
+    };
+
+    href.append_to_uri(page_uri)
+        .is_ok_and(|uri| {
+            outputs.contains(uri.path())
+                || resolver.get_path_from_uri(&uri).is_ok_and(|path| path.exists())
+        })
+}
+
+/// Every URI path an output is served at, including the folder of
+/// an `index.html`.
+#[expect(clippy::single_call_fn, reason = "names what `check_jobs` collects the outputs into")]
+fn output_uri_paths(output: &Path) -> Vec<String> {
+    let uri_path = format!("/{}", output.to_string_lossy().replace('\\', "/"));
+    let mut paths = vec![];
+
+    if let Some(dir_path) = uri_path.strip_suffix("index.html") {
+        paths.push(dir_path.to_owned());
+
+        if dir_path.len() > 1 {
+            paths.push(dir_path.trim_end_matches('/').to_owned());
+        }
+    }
+
+    paths.push(uri_path);
+
+    paths
+}
@@ -0,0 +1,124 @@
+use super::cache::BuildCache;
+use super::config::GenerateConfig;
+use super::generate::{compressed_path, plan_jobs};
+use super::manifest::{Manifest, MANIFEST_FILE};
+use color_eyre::Result;
+use reqaz::compress::Encoding;
+use reqaz::source::SourceResolver;
+use std::fs::{remove_dir, remove_dir_all, remove_file};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+
+/// Remove outputs of previous builds that the current config no
+/// longer produces. With `all`, every output is removed, along with
+/// the manifest and the build cache. Only files listed in the
+/// manifest are touched, so files placed in the output directory
+/// by hand are safe.
+///
+/// With `dry_run`, nothing is removed, and what would be is listed.
+#[expect(clippy::print_stdout, reason = "listing what's removed is the command's output")]
+#[expect(clippy::single_call_fn, reason = "the `clean` subcommand's entry point")]
+pub fn run(
+    resolver: &SourceResolver,
+    generate: &GenerateConfig,
+    dry_run: bool,
+    all: bool
+) -> Result<ExitCode> {
+    let output_dir = &generate.output_dir;
+    let manifest = Manifest::load(output_dir);
+
+    let outputs = if all {
+        manifest.all()
+    } else {
+        manifest.stale(&plan_jobs(resolver, generate)?.0)
+    };
+
+    if dry_run {
+        for output in &outputs {
+            println!("Would remove {}", output.display());
+        }
+
+        println!("Would remove {} outputs.", outputs.len());
+
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let removed = prune(output_dir, &outputs)?;
+
+    for output in &removed {
+        println!("Removed {}", output.display());
+    }
+
+    if all {
+        remove_if_exists(&output_dir.join(MANIFEST_FILE))?;
+
+        if let Err(err) = remove_dir_all(BuildCache::dir_for(output_dir)) {
+            if err.kind() != ErrorKind::NotFound {
+                return Err(err.into());
+            }
+        }
+    } else {
+        // Keep the manifest in step, so the same outputs aren't
+        // listed again
+        let kept = Manifest {
+            outputs: manifest.outputs
+                .into_iter()
+                .filter(|entry| !outputs.contains(&entry.output))
+                .collect()
+        };
+
+        if output_dir.is_dir() {
+            kept.save(output_dir)?;
+        }
+    }
+
+    println!("Removed {} outputs.", removed.len());
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Remove outputs from the output directory, along with their
+/// compressed copies and any folders left empty, returning the
+/// outputs that existed.
+///
+/// # Errors
+///
+/// Errors if an output exists but can't be removed.
+pub fn prune(output_dir: &Path, outputs: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut removed = vec![];
+
+    for output in outputs {
+        let out_path = output_dir.join(output);
+
+        for encoding in Encoding::ALL {
+            remove_if_exists(&compressed_path(&out_path, encoding))?;
+        }
+
+        if !remove_if_exists(&out_path)? {
+            continue;
+        }
+
+        // Folders that still have files in them fail to be removed,
+        // which stops the walk up
+        for dir in output.ancestors().skip(1) {
+            if dir.as_os_str().is_empty() || remove_dir(output_dir.join(dir)).is_err() {
+                break;
+            }
+        }
+
+        removed.push(output.clone());
+    }
+
+    Ok(removed)
+}
+
+/// Remove a file, returning whether it existed.
+fn remove_if_exists(path: &Path) -> Result<bool> {
+    match remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err.into())
+    }
+}
@@ -0,0 +1,420 @@
+#![expect(clippy::module_name_repetitions, reason = "each config is named for the part of reqaz it configures")]
+
+extern crate alloc;
+
+use alloc::collections::BTreeMap;
+use crate::Cli;
+use color_eyre::eyre::WrapErr as _;
+use color_eyre::Result;
+use core::str::FromStr as _;
+use clap::ValueEnum;
+use core::net::{IpAddr, Ipv4Addr, SocketAddr};
+use http::uri::{Authority, Uri};
+use reqaz::caching::CacheControl;
+use reqaz::compress::{Compression, Encoding};
+use reqaz::html::ModChains;
+use reqaz::proxy::Proxy;
+use serde::{Serialize, Deserialize};
+use std::path::PathBuf;
+
+
+/// The `Cache-Control` value for files no rule matches, so browsers
+/// always revalidate.
+const DEFAULT_CACHE_CONTROL: &str = "no-cache";
+
+/// Base CLI configuration (reqaz.json).
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CliConfig {
+    /// `Cache-Control` headers to send, first matching rule wins.
+    /// Files no rule matches get `no-cache`.
+    pub cache_control: Vec<CacheControlRule>,
+
+    /// The mods to run on pages under a directory, relative to root.
+    pub dir_mods: BTreeMap<PathBuf, Vec<String>>,
+
+    /// Whether symlinks under root can point outside of it.
+    pub follow_symlinks: bool,
+
+    /// Generate options.
+    pub generate: Option<GenerateConfig>,
+
+    /// The address to serve on. Use `0.0.0.0` to serve on every interface.
+    pub host: IpAddr,
+
+    /// Reload open pages when files change (serve only).
+    pub live_reload: bool,
+
+    /// Enable logging.
+    pub log: bool,
+
+    /// The mods to run on pages, in order. Defaults to
+    /// `["query", "fetch", "css", "component"]`.
+    pub mods: Option<Vec<String>>,
+
+    /// The port to serve from.
+    pub port: u16,
+
+    /// The HTTP version to serve.
+    pub protocol: Protocol,
+
+    /// Servers to forward requests to, keyed by path prefix,
+    /// like `"/api/": "http://localhost:8080"` (serve only).
+    pub proxy: BTreeMap<String, String>,
+
+    /// The root folder to serve from.
+    pub root: Option<PathBuf>
+}
+
+impl CliConfig {
+    /// The authority pages see the server at, for fetches by path.
+    /// That's localhost unless the server is bound to a specific
+    /// address.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the authority isn't valid.
+    pub fn authority(&self) -> Result<Authority> {
+        let authority = if self.host.is_unspecified() || self.host.is_loopback() {
+            Authority::from_str(&format!("localhost:{}", self.port))?
+        } else {
+            Authority::from_str(&SocketAddr::new(self.host, self.port).to_string())?
+        };
+
+        Ok(authority)
+    }
+
+    /// The `Cache-Control` rules, as configured.
+    ///
+    /// # Errors
+    ///
+    /// Errors if a glob is invalid.
+    pub fn cache_control(&self) -> Result<CacheControl> {
+        let rules = self.cache_control
+            .iter()
+            .map(|rule| (rule.path.clone(), rule.value.clone()));
+
+        Ok(
+            CacheControl::new(rules)
+                .wrap_err("Invalid cache_control glob")?
+                .with_fallback(DEFAULT_CACHE_CONTROL.to_owned())
+        )
+    }
+
+    /// The mods to run on pages, as configured.
+    pub fn mod_chains(&self) -> ModChains {
+        let base = self.mods
+            .clone()
+            .map_or_else(ModChains::default, ModChains::new);
+
+        self.dir_mods
+            .iter()
+            .fold(base, |chains, (dir, mods)| chains.with_dir(dir.clone(), mods.clone()))
+    }
+
+    /// Override config with CLI options manually.
+    pub fn override_with_cli(mut self, cli: &Cli) -> Self {
+        if let Some(root) = cli.path.clone() {
+            self.root = Some(root);
+        }
+
+        if let Some(host) = cli.host {
+            self.host = host;
+        }
+
+        if let Some(port) = cli.port {
+            self.port = port;
+        }
+
+        if let Some(protocol) = cli.protocol {
+            self.protocol = protocol;
+        }
+
+        if let Some(log) = cli.log {
+            self.log = log;
+        }
+
+        if let Some(live_reload) = cli.live_reload {
+            self.live_reload = live_reload;
+        }
+
+        if let (Some(jobs), Some(generate)) = (cli.jobs, self.generate.as_mut()) {
+            generate.jobs = Some(jobs);
+        }
+
+        self
+    }
+
+    /// The proxy rules, as configured, if there are any.
+    ///
+    /// # Errors
+    ///
+    /// Errors if a target isn't a valid URI.
+    pub fn proxy(&self) -> Result<Option<Proxy>> {
+        if self.proxy.is_empty() {
+            return Ok(None);
+        }
+
+        let rules = self.proxy
+            .iter()
+            .map(|(prefix, target)| {
+                let target_uri = Uri::from_str(target)
+                    .wrap_err_with(|| format!("Invalid proxy target for `{prefix}`"))?;
+
+                Ok((prefix.clone(), target_uri))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Some(Proxy::new(rules)))
+    }
+}
+
+impl Default for CliConfig {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            root: None,
+            follow_symlinks: false,
+            host: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port: 5000,
+            protocol: Protocol::default(),
+            log: false,
+            live_reload: true,
+            mods: None,
+            dir_mods: BTreeMap::new(),
+            proxy: BTreeMap::new(),
+            cache_control: vec![],
+            generate: None
+        }
+    }
+}
+
+/// The HTTP version the dev server speaks.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Protocol {
+    /// Whichever the client speaks.
+    #[default]
+    Auto,
+
+    /// HTTP/1.1 only.
+    Http1,
+
+    /// HTTP/2 only, without TLS (prior knowledge).
+    Http2
+}
+
+/// A `Cache-Control` value for the files a glob matches.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CacheControlRule {
+    /// The glob to match, relative to root, like `**/*.html`.
+    pub path: String,
+
+    /// The header value, like `public, max-age=31536000, immutable`.
+    pub value: String
+}
+
+/// Generation configuration.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GenerateConfig {
+    /// Copy static assets from root into the output dir.
+    pub assets: Option<AssetsConfig>,
+
+    /// Write compressed copies next to outputs.
+    pub compress: Option<CompressConfig>,
+
+    /// Copy assets under names with a content hash in them.
+    pub fingerprint: Option<FingerprintConfig>,
+
+    /// How many outputs to build at once, defaults to the number of CPUs.
+    pub jobs: Option<usize>,
+
+    /// The final output directory of files.
+    pub output_dir: PathBuf,
+
+    /// Pages to discover under root, generated alongside the pipelines.
+    pub pages: Option<PagesConfig>,
+
+    /// List of pipelines to run.
+    #[serde(default)]
+    pub pipelines: Vec<PipelineConfig>
+}
+
+/// Pipeline configuration.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PipelineConfig {
+    /// The input URI.
+    #[serde(with = "http_serde::uri")]
+    pub input: Uri,
+
+    /// The mods to run on the page, in order, instead of the
+    /// root-level ones.
+    #[serde(default)]
+    pub mods: Option<Vec<String>>,
+
+    /// The output path, relative to the output dir.
+    pub output: PathBuf
+}
+
+/// Page discovery configuration.
+///
+/// Either `"auto"` to generate every page under root, or globs
+/// matched against page paths relative to root.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum PagesConfig {
+    /// Generate every page.
+    Auto(AutoPages),
+
+    /// Generate pages matching globs.
+    Globs(PageGlobs)
+}
+
+impl PagesConfig {
+    /// Get the globs pages are filtered by.
+    #[expect(clippy::pattern_type_mismatch, reason = "the globs are cloned out of the borrow")]
+    pub fn globs(&self) -> PageGlobs {
+        match self {
+            Self::Auto(_) => PageGlobs::default(),
+            Self::Globs(globs) => globs.clone()
+        }
+    }
+}
+
+/// The `"auto"` page discovery mode.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoPages {
+    /// Generate every page.
+    Auto
+}
+
+/// Globs to filter discovered pages by.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PageGlobs {
+    /// Pages must not match any of these.
+    pub exclude: Vec<String>,
+
+    /// Pages must match at least one of these.
+    pub include: Vec<String>
+}
+
+impl Default for PageGlobs {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            include: vec!["**".to_owned()],
+            exclude: vec![]
+        }
+    }
+}
+
+/// Static asset copying configuration.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct AssetsConfig {
+    /// Globs of assets not to copy, relative to root.
+    pub ignore: Vec<String>
+}
+
+/// Precompressed output configuration.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CompressConfig {
+    /// The algorithms to compress with, in order of preference.
+    pub algorithms: Vec<CompressAlgorithm>,
+
+    /// Outputs smaller than this many bytes aren't compressed.
+    pub min_size: usize,
+
+    /// The quality to compress with, highest if unset.
+    pub quality: Option<u32>
+}
+
+impl CompressConfig {
+    /// The compression settings, as configured.
+    #[expect(clippy::single_call_fn, reason = "turns the config into the library's settings")]
+    pub fn compression(&self) -> Compression {
+        let encodings = self.algorithms
+            .iter()
+            .map(|algorithm| match *algorithm {
+                CompressAlgorithm::Brotli => Encoding::Brotli,
+                CompressAlgorithm::Gzip => Encoding::Gzip
+            })
+            .collect();
+
+        Compression::new(encodings)
+            .with_quality(self.quality)
+            .with_min_size(self.min_size)
+    }
+}
+
+impl Default for CompressConfig {
+    #[inline]
+    fn default() -> Self {
+        let base = Compression::default();
+
+        Self {
+            algorithms: vec![CompressAlgorithm::Brotli, CompressAlgorithm::Gzip],
+            min_size: base.min_size,
+            quality: base.quality
+        }
+    }
+}
+
+/// A compression algorithm for precompressed outputs.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum CompressAlgorithm {
+    /// Brotli, written as `.br`.
+    #[serde(alias = "br")]
+    Brotli,
+
+    /// Gzip, written as `.gz`.
+    #[serde(alias = "gz")]
+    Gzip
+}
+
+/// Asset fingerprinting configuration.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct FingerprintConfig {
+    /// Globs of assets to keep their names, relative to root.
+    pub exclude: Vec<String>
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used, reason = "a failed unwrap fails the test")]
+mod tests {
+    use super::CliConfig;
+    use crate::Cli;
+    use clap::Parser as _;
+
+    fn config(args: &[&str]) -> CliConfig {
+        CliConfig::default().override_with_cli(&Cli::parse_from(args))
+    }
+
+    #[test]
+    fn address_from_cli() {
+        let defaults = config(&["reqaz"]);
+
+        assert_eq!(defaults.host.to_string(), "127.0.0.1");
+        assert_eq!(defaults.authority().unwrap(), "localhost:5000");
+
+        let cli = config(&["reqaz", "--host", "192.168.1.20", "--port", "8080"]);
+
+        assert_eq!(cli.host.to_string(), "192.168.1.20");
+        assert_eq!(cli.port, 8080);
+    }
+
+    #[test]
+    fn authority_for_address() {
+        let authority = |args: &[&str]| config(args).authority().unwrap().to_string();
+
+        assert_eq!(authority(&["reqaz", "--host", "0.0.0.0", "-p", "8080"]), "localhost:8080");
+        assert_eq!(authority(&["reqaz", "--host", "::1"]), "localhost:5000");
+        assert_eq!(authority(&["reqaz", "--host", "192.168.1.20", "-p", "8080"]), "192.168.1.20:8080");
+        assert_eq!(authority(&["reqaz", "--host", "fe80::1"]), "[fe80::1]:5000");
+    }
+}
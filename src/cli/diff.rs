@@ -0,0 +1,191 @@
+use super::config::GenerateConfig;
+use super::fingerprint::Fingerprints;
+use super::generate::{plan_jobs, BuildJob};
+use super::manifest::Manifest;
+use color_eyre::owo_colors::OwoColorize as _;
+use color_eyre::Result;
+use eyre::Report;
+use reqaz::mediatype::TEXT_HTML;
+use reqaz::source::SourceResolver;
+use similar::TextDiff;
+use std::fs::read;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use tokio::task::spawn_blocking;
+
+
+/// How many unchanged lines to show around each change.
+const CONTEXT_LINES: usize = 3;
+
+/// Everything a build would change in the output directory.
+#[expect(clippy::module_name_repetitions, reason = "a bare `Diff` would clash with `similar`'s")]
+pub struct BuildDiff {
+    /// Outputs that don't exist yet.
+    pub added: Vec<PathBuf>,
+
+    /// Outputs whose contents would change, with a unified diff
+    /// if they're HTML.
+    pub changed: Vec<ChangedOutput>,
+
+    /// Inputs that failed to resolve, with what went wrong.
+    pub failures: Vec<(String, Report)>,
+
+    /// Outputs of previous builds no pipeline produces anymore.
+    pub removed: Vec<PathBuf>,
+
+    /// How many outputs would stay the same.
+    pub unchanged: usize
+}
+
+/// An output whose contents would change.
+pub struct ChangedOutput {
+    /// A unified diff of the change, for HTML outputs.
+    pub diff: Option<String>,
+
+    /// The output path, relative to the output directory.
+    pub output: PathBuf
+}
+
+impl BuildDiff {
+    /// The process exit code, non-zero if anything failed to resolve.
+    pub const fn exit_code(&self) -> ExitCode {
+        if self.failures.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        }
+    }
+
+    /// Print every change, followed by a summary.
+    #[expect(clippy::print_stderr, reason = "resolve errors go to stderr, like the build's")]
+    #[expect(clippy::print_stdout, reason = "the changes and summary are the command's output")]
+    #[expect(clippy::pattern_type_mismatch, reason = "failures are borrowed pairs")]
+    pub fn print(&self) {
+        for (input, error) in &self.failures {
+            eprintln!("{} {input}", "error:".red().bold());
+            eprintln!("    {error:#}");
+        }
+
+        for output in &self.added {
+            println!("{} {}", "added  ".green(), output.display());
+        }
+
+        for changed in &self.changed {
+            println!("{} {}", "changed".yellow(), changed.output.display());
+        }
+
+        for output in &self.removed {
+            println!("{} {}", "removed".red(), output.display());
+        }
+
+        for diff in self.changed.iter().filter_map(|changed| changed.diff.as_ref()) {
+            println!();
+
+            for line in diff.lines() {
+                if line.starts_with("+++") || line.starts_with("---") {
+                    println!("{}", line.bold());
+                } else if line.starts_with('+') {
+                    println!("{}", line.green());
+                } else if line.starts_with('-') {
+                    println!("{}", line.red());
+                } else if line.starts_with("@@") {
+                    println!("{}", line.cyan());
+                } else {
+                    println!("{line}");
+                }
+            }
+        }
+
+        println!(
+            "{} added, {} changed, {} removed, {} unchanged.",
+            self.added.len(),
+            self.changed.len(),
+            self.removed.len(),
+            self.unchanged
+        );
+    }
+}
+
+/// Resolve every pipeline and asset in memory, and print how the
+/// result differs from what's in the output directory. Nothing is
+/// written.
+#[expect(clippy::single_call_fn, reason = "the `diff` subcommand's entry point")]
+pub async fn run(resolver: &SourceResolver, generate: &GenerateConfig) -> Result<ExitCode> {
+    let diff_resolver = resolver.clone();
+    let diff_generate = generate.clone();
+
+    let diff = spawn_blocking(move || {
+        let (jobs, fingerprints) = plan_jobs(&diff_resolver, &diff_generate)?;
+
+        diff_jobs(&diff_resolver, &jobs, fingerprints.as_ref(), &diff_generate.output_dir)
+    }).await??;
+
+    diff.print();
+
+    Ok(diff.exit_code())
+}
+
+/// Build every job in memory and compare it to the output directory.
+#[expect(clippy::single_call_fn, reason = "the blocking half of `run`")]
+fn diff_jobs(
+    resolver: &SourceResolver,
+    jobs: &[BuildJob],
+    fingerprints: Option<&Fingerprints>,
+    output_dir: &Path
+) -> Result<BuildDiff> {
+    let mut diff = BuildDiff {
+        added: vec![],
+        changed: vec![],
+        failures: vec![],
+        removed: Manifest::load(output_dir)
+            .stale(jobs)
+            .into_iter()
+            .filter(|output| output_dir.join(output).is_file())
+            .collect(),
+        unchanged: 0
+    };
+
+    for job in jobs {
+        let (body, cached) = match job.render(&job.resolver_for(resolver), fingerprints) {
+            Ok(rendered) => rendered,
+            Err(error) => {
+                diff.failures.push((job.input(), error));
+                continue;
+            }
+        };
+
+        let existing = match read(output_dir.join(job.output())) {
+            Ok(existing) => existing,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                diff.added.push(job.output().to_path_buf());
+                continue;
+            },
+            Err(err) => return Err(err.into())
+        };
+
+        if existing == body {
+            diff.unchanged = diff.unchanged.saturating_add(1);
+            continue;
+        }
+
+        let unified = (cached.mime == TEXT_HTML.to_string()).then(|| {
+            let name = job.output().display().to_string();
+            let old = String::from_utf8_lossy(&existing);
+            let new = String::from_utf8_lossy(&body);
+
+            TextDiff::from_lines(old.as_ref(), new.as_ref())
+                .unified_diff()
+                .context_radius(CONTEXT_LINES)
+                .header(&name, &name)
+                .to_string()
+        });
+
+        diff.changed.push(ChangedOutput {
+            diff: unified,
+            output: job.output().to_path_buf()
+        });
+    }
+
+    Ok(diff)
+}
@@ -0,0 +1,465 @@
+extern crate alloc;
+
+use super::cache::hex_digest;
+use super::config::FingerprintConfig;
+use super::generate::{glob_set, BuildJob};
+use alloc::collections::{BTreeMap, BTreeSet};
+use color_eyre::eyre::WrapErr as _;
+use color_eyre::Result;
+use core::mem::take;
+use http::uri::Uri;
+use kuchikiki::traits::TendrilSink as _;
+use reqaz::html::attr::Href;
+use reqaz::mediatype::{TEXT_CSS, TEXT_HTML};
+use reqaz::source::SourceResolver;
+use std::fs::read;
+use std::path::{Path, PathBuf};
+
+
+/// How many hex characters of the content hash go in a file name.
+const HASH_LENGTH: usize = 8;
+
+/// HTML attributes holding a single reference.
+const REFERENCE_ATTRS: [&str; 3] = ["href", "src", "poster"];
+
+/// Assets that are looked up by their name rather than linked to,
+/// so they always keep it.
+const FIXED_NAMES: [&str; 9] = [
+    "robots.txt",
+    "favicon.ico",
+    "sitemap.xml",
+    "manifest.webmanifest",
+    "browserconfig.xml",
+    "humans.txt",
+    "ads.txt",
+    "CNAME",
+    "apple-touch-icon*.png"
+];
+
+/// Fingerprinted asset names, and rewriting references to them.
+pub struct Fingerprints {
+    /// Hashed output paths, keyed by the original, both as URI paths.
+    names: BTreeMap<String, String>
+}
+
+impl Fingerprints {
+    /// Give every asset job not excluded by the config a hashed
+    /// output name, returning the names given. Assets like
+    /// `robots.txt`, which are found by their name, are always
+    /// excluded.
+    ///
+    /// Stylesheets are named last, after the references in them
+    /// are rewritten, so their names change when an image they
+    /// point to does. One pointing to another stylesheet is named
+    /// after it, for the same reason.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the exclude globs are invalid, an asset can't be
+    /// read, or a stylesheet fails to build.
+    #[expect(clippy::single_call_fn, reason = "fingerprinting is a build step of its own")]
+    pub fn assign(
+        resolver: &SourceResolver,
+        jobs: &mut [BuildJob],
+        config: &FingerprintConfig
+    ) -> Result<Self> {
+        let fixed = FIXED_NAMES.iter().map(|&name| name.to_owned());
+        let exclude = glob_set(&config.exclude.iter().cloned().chain(fixed).collect::<Vec<_>>())?;
+
+        let mut fingerprints = Self {
+            names: BTreeMap::new()
+        };
+
+        let (stylesheets, others): (Vec<&mut BuildJob>, Vec<&mut BuildJob>) = jobs.iter_mut()
+            .filter(|job| job.asset_path().is_some_and(|path| !exclude.is_match(path)))
+            .partition(|job| job.output().extension().is_some_and(|ext| ext.eq_ignore_ascii_case("css")));
+
+        for job in others {
+            let path = job.source_path(resolver);
+            let contents = read(&path)
+                .wrap_err_with(|| format!("Failed to read `{}` to fingerprint it", path.display()))?;
+
+            fingerprints.rename(job, &hex_digest(&contents));
+        }
+
+        let mut pending = stylesheets;
+
+        while !pending.is_empty() {
+            let pending_paths = pending.iter()
+                .map(|job| uri_path(job.output()))
+                .collect::<BTreeSet<_>>();
+
+            let mut ready = vec![];
+            let mut waiting = vec![];
+
+            for job in pending {
+                let (body, _) = job.render(resolver, Some(&fingerprints))?;
+
+                if points_to(&body, job.output(), &pending_paths) {
+                    waiting.push((job, body));
+                } else {
+                    ready.push((job, body));
+                }
+            }
+
+            // Stylesheets pointing to each other in a cycle can't
+            // be ordered, so they're named together
+            if ready.is_empty() {
+                ready = take(&mut waiting);
+            }
+
+            for (job, body) in ready {
+                fingerprints.rename(job, &hex_digest(&body));
+            }
+
+            pending = waiting.into_iter().map(|(job, _)| job).collect();
+        }
+
+        Ok(fingerprints)
+    }
+
+    /// A digest of the names given to the paths an output
+    /// references, so it's rebuilt only when one of those changes.
+    /// Paths without a fingerprint count too, so one gaining a
+    /// fingerprint later also rebuilds the output.
+    pub fn key(&self, references: &BTreeSet<String>) -> String {
+        let names = references
+            .iter()
+            .map(|original| format!("{original}={}", self.names.get(original).map_or("", String::as_str)))
+            .collect::<Vec<_>>()
+            .join(";");
+
+        hex_digest(names.as_bytes())
+    }
+
+    /// Give a job a hashed output name.
+    fn rename(&mut self, job: &mut BuildJob, hash: &str) {
+        let hashed = hashed_path(job.output(), hash);
+
+        self.names.insert(uri_path(job.output()), uri_path(&hashed));
+        job.set_output(hashed);
+    }
+
+    /// Rewrite references to fingerprinted assets in a built
+    /// output, if it's HTML or CSS, returning it along with every
+    /// local path it references, as URI paths. `output` is where
+    /// the output is served, which relative references resolve
+    /// against.
+    pub fn rewrite(&self, body: Vec<u8>, mime: &str, output: &Path) -> (Vec<u8>, BTreeSet<String>) {
+        let mut references = BTreeSet::new();
+
+        let Ok(base) = uri_path(output).parse::<Uri>() else {
+            return (body, references);
+        };
+
+        let rewritten = if mime == TEXT_CSS.to_string() {
+            self.rewrite_css(&String::from_utf8_lossy(&body), &base, &mut references).into_bytes()
+        } else if mime == TEXT_HTML.to_string() {
+            self.rewrite_html(&String::from_utf8_lossy(&body), &base, &mut references).into_bytes()
+        } else {
+            body
+        };
+
+        (rewritten, references)
+    }
+
+    /// Rewrite every `url()` in a stylesheet.
+    fn rewrite_css(&self, css: &str, base: &Uri, references: &mut BTreeSet<String>) -> String {
+        map_css_urls(css, |reference| self.rewrite_reference(reference, base, references))
+    }
+
+    /// Rewrite `href`, `src`, `poster` and `srcset` attributes, and
+    /// `url()` in styles, across an HTML page.
+    fn rewrite_html(&self, html: &str, base: &Uri, references: &mut BTreeSet<String>) -> String {
+        let document = kuchikiki::parse_html().one(html);
+
+        for node in document.descendants() {
+            if let Some(element) = node.as_element() {
+                let Ok(mut attributes) = element.attributes.try_borrow_mut() else {
+                    continue;
+                };
+
+                for name in REFERENCE_ATTRS {
+                    let rewritten = attributes.get(name)
+                        .and_then(|value| self.rewrite_reference(value, base, references));
+
+                    if let Some(value) = rewritten {
+                        attributes.insert(name, value);
+                    }
+                }
+
+                let srcset = attributes.get("srcset")
+                    .map(|value| self.rewrite_srcset(value, base, references));
+
+                if let Some(value) = srcset {
+                    attributes.insert("srcset", value);
+                }
+
+                let style = attributes.get("style")
+                    .map(|value| self.rewrite_css(value, base, references));
+
+                if let Some(value) = style {
+                    attributes.insert("style", value);
+                }
+            }
+
+            if let Some(text) = node.as_text() {
+                let in_style = node.parent()
+                    .and_then(|parent| parent.as_element().map(|element| &*element.name.local == "style"))
+                    .unwrap_or(false);
+
+                if in_style {
+                    let rewritten = self.rewrite_css(&text.borrow(), base, references);
+                    text.replace(rewritten);
+                }
+            }
+        }
+
+        document.to_string()
+    }
+
+    /// The rewritten form of a reference, if it points to a
+    /// fingerprinted asset. Only the file name changes, so relative
+    /// references stay relative, and queries and fragments are kept.
+    /// Local references are added to `references`.
+    fn rewrite_reference(&self, reference: &str, base: &Uri, references: &mut BTreeSet<String>) -> Option<String> {
+        let split_at = reference.find(['?', '#']).unwrap_or(reference.len());
+        let (path, suffix) = reference.split_at(split_at);
+
+        let resolved = resolve_reference(path, base)?;
+        let hashed = self.names.get(&resolved);
+        references.insert(resolved);
+
+        let hashed_name = hashed?.rsplit('/').next()?;
+
+        let dir = path.rfind('/').map_or("", |idx| path.get(..=idx).unwrap_or_default());
+
+        Some(format!("{dir}{hashed_name}{suffix}"))
+    }
+
+    /// Rewrite every candidate in a `srcset`.
+    fn rewrite_srcset(&self, srcset: &str, base: &Uri, references: &mut BTreeSet<String>) -> String {
+        srcset.split(',')
+            .map(|candidate| {
+                let trimmed = candidate.trim();
+                let (reference, descriptor) = trimmed
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((trimmed, ""));
+
+                let hashed = self.rewrite_reference(reference, base, references)
+                    .unwrap_or_else(|| reference.to_owned());
+
+                if descriptor.is_empty() {
+                    hashed
+                } else {
+                    format!("{hashed} {}", descriptor.trim())
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Replace every `url()` in a stylesheet that `replace` gives a new
+/// reference for, keeping its quotes.
+fn map_css_urls<F: FnMut(&str) -> Option<String>>(css: &str, mut replace: F) -> String {
+    let mut rewritten = String::with_capacity(css.len());
+    let mut rest = css;
+
+    while let Some(start) = rest.find("url(") {
+        let (before, after_start) = rest.split_at(start.saturating_add(4));
+        rewritten.push_str(before);
+
+        let Some(end) = after_start.find(')') else {
+            rest = after_start;
+            break;
+        };
+
+        let (inner, after) = after_start.split_at(end);
+        let trimmed = inner.trim();
+        let quote = trimmed.chars().next().filter(|chr| matches!(chr, '"' | '\''));
+
+        let reference = quote.map_or(trimmed, |quote_chr| trimmed.trim_matches(quote_chr));
+
+        match replace(reference) {
+            Some(hashed) => rewritten.extend(quote.into_iter().chain(hashed.chars()).chain(quote)),
+            None => rewritten.push_str(inner)
+        }
+
+        rest = after;
+    }
+
+    rewritten.push_str(rest);
+
+    rewritten
+}
+
+/// The URI path a local reference resolves to, against where the
+/// output pointing to it is served, with `.` and `..` folded away.
+fn resolve_reference(path: &str, base: &Uri) -> Option<String> {
+    let href @ (Href::Absolute(_) | Href::Relative(_)) = Href::try_from(path).ok()? else {
+        return None;
+    };
+
+    let resolved = href.append_to_uri(base).ok()?;
+    let mut segments: Vec<&str> = vec![];
+
+    for segment in resolved.path().split('/') {
+        match segment {
+            "" | "." => {},
+            ".." => {
+                segments.pop();
+            },
+            _ => segments.push(segment)
+        }
+    }
+
+    Some(format!("/{}", segments.join("/")))
+}
+
+/// Whether a built stylesheet points to any of the `pending` URI
+/// paths, other than its own.
+#[cfg_attr(not(test), expect(clippy::single_call_fn, reason = "split from `assign` so it can be tested"))]
+fn points_to(body: &[u8], output: &Path, pending: &BTreeSet<String>) -> bool {
+    let own = uri_path(output);
+
+    let Ok(base) = own.parse::<Uri>() else {
+        return false;
+    };
+
+    let mut found = false;
+
+    map_css_urls(&String::from_utf8_lossy(body), |reference| {
+        let path = reference.split(['?', '#']).next().unwrap_or_default();
+
+        found |= resolve_reference(path, &base)
+            .is_some_and(|resolved| resolved != own && pending.contains(&resolved));
+
+        None
+    });
+
+    found
+}
+
+/// Where an output is served, as a URI path.
+fn uri_path(output: &Path) -> String {
+    format!("/{}", output.to_string_lossy().replace('\\', "/"))
+}
+
+/// An output path with a hash before its extension, like
+/// `logo.3f9a1c2b.png`.
+#[cfg_attr(not(test), expect(clippy::single_call_fn, reason = "split from `rename` so it can be tested"))]
+fn hashed_path(output: &Path, hash: &str) -> PathBuf {
+    let short_hash = hash.get(..HASH_LENGTH).unwrap_or(hash);
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+
+    let name = output.extension().map_or_else(
+        || format!("{stem}.{short_hash}"),
+        |ext| format!("{stem}.{short_hash}.{}", ext.to_string_lossy())
+    );
+
+    output.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hashed_path, points_to, Fingerprints};
+    use super::alloc::collections::{BTreeMap, BTreeSet};
+    use http::uri::Uri;
+    use std::path::{Path, PathBuf};
+
+    fn fingerprints() -> Fingerprints {
+        Fingerprints {
+            names: BTreeMap::from([
+                ("/img/logo.png".to_owned(), "/img/logo.3f9a1c2b.png".to_owned()),
+                ("/css/base.css".to_owned(), "/css/base.0d1e2f3a.css".to_owned())
+            ])
+        }
+    }
+
+    fn base() -> Uri {
+        Uri::from_static("/css/site.css")
+    }
+
+    #[test]
+    fn reference_relative() {
+        let rewritten = fingerprints().rewrite_reference("../img/logo.png", &base(), &mut BTreeSet::new());
+
+        assert_eq!(rewritten.as_deref(), Some("../img/logo.3f9a1c2b.png"));
+    }
+
+    #[test]
+    fn reference_absolute() {
+        let rewritten = fingerprints().rewrite_reference("/img/logo.png", &base(), &mut BTreeSet::new());
+
+        assert_eq!(rewritten.as_deref(), Some("/img/logo.3f9a1c2b.png"));
+    }
+
+    #[test]
+    fn reference_keeps_query_and_fragment() {
+        let rewritten = fingerprints().rewrite_reference("/img/logo.png?v=2#top", &base(), &mut BTreeSet::new());
+
+        assert_eq!(rewritten.as_deref(), Some("/img/logo.3f9a1c2b.png?v=2#top"));
+    }
+
+    #[test]
+    fn reference_skips_others() {
+        let fingerprints = fingerprints();
+
+        assert_eq!(fingerprints.rewrite_reference("data:image/png;base64,iVBORw0KGgo=", &base(), &mut BTreeSet::new()), None);
+        assert_eq!(fingerprints.rewrite_reference("https://example.com/img/logo.png", &base(), &mut BTreeSet::new()), None);
+        assert_eq!(fingerprints.rewrite_reference("/img/other.png", &base(), &mut BTreeSet::new()), None);
+    }
+
+    #[test]
+    fn css_urls() {
+        let css = "@import url('base.css');\nh1 { background: url(\"../img/logo.png\"); }\nh2 { background: url(/img/other.png); }";
+        let mut references = BTreeSet::new();
+        let rewritten = fingerprints().rewrite_css(css, &base(), &mut references);
+
+        assert_eq!(rewritten, "@import url('base.0d1e2f3a.css');\nh1 { background: url(\"../img/logo.3f9a1c2b.png\"); }\nh2 { background: url(/img/other.png); }");
+        assert_eq!(references, BTreeSet::from([
+            "/css/base.css".to_owned(),
+            "/img/logo.png".to_owned(),
+            "/img/other.png".to_owned()
+        ]));
+    }
+
+    #[test]
+    fn key_covers_only_references() {
+        let before = fingerprints();
+        let mut after = fingerprints();
+        after.names.insert("/css/base.css".to_owned(), "/css/base.9e8d7c6b.css".to_owned());
+
+        let logo = BTreeSet::from(["/img/logo.png".to_owned()]);
+        let base_css = BTreeSet::from(["/css/base.css".to_owned()]);
+
+        assert_eq!(before.key(&logo), after.key(&logo));
+        assert_ne!(before.key(&base_css), after.key(&base_css));
+    }
+
+    #[test]
+    fn srcset_candidates() {
+        let rewritten = fingerprints().rewrite_srcset("/img/logo.png 1x,  /img/other.png   2x", &base(), &mut BTreeSet::new());
+
+        assert_eq!(rewritten, "/img/logo.3f9a1c2b.png 1x, /img/other.png 2x");
+    }
+
+    #[test]
+    fn hashed_paths() {
+        let hash = "3f9a1c2b4d5e6f708192a3b4c5d6e7f8";
+
+        assert_eq!(hashed_path(Path::new("img/logo.png"), hash), PathBuf::from("img/logo.3f9a1c2b.png"));
+        assert_eq!(hashed_path(Path::new("LICENSE"), hash), PathBuf::from("LICENSE.3f9a1c2b"));
+        assert_eq!(hashed_path(Path::new("app.min.js"), "abc"), PathBuf::from("app.min.abc.js"));
+    }
+
+    #[test]
+    fn stylesheets_wait_on_others() {
+        let pending = BTreeSet::from(["/css/site.css".to_owned(), "/css/base.css".to_owned()]);
+
+        assert!(points_to(b"@import url(base.css);", Path::new("css/site.css"), &pending));
+        assert!(!points_to(b"@import url(base.0d1e2f3a.css);", Path::new("css/site.css"), &pending));
+        assert!(!points_to(b"h1 { background: url(site.css#self); }", Path::new("css/site.css"), &pending));
+    }
+}
@@ -0,0 +1,682 @@
+extern crate alloc;
+
+use alloc::collections::BTreeSet;
+use alloc::sync::Arc;
+use super::cache::{BuildCache, CachedOutput};
+use super::clean::prune;
+use super::config::{AssetsConfig, CompressConfig, GenerateConfig, PipelineConfig};
+use super::fingerprint::Fingerprints;
+use super::manifest::Manifest;
+use super::report::BuildReport;
+use color_eyre::owo_colors::OwoColorize as _;
+use color_eyre::Result;
+use core::iter::once;
+use core::num::NonZeroUsize;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use http::uri::Uri;
+use reqaz::compress::{Compression, Encoding};
+use reqaz::mediatype::{GetMediaType as _, APPLICATION_OCTET_STREAM};
+use reqaz::source::SourceResolver;
+use reqaz::source::discover::Asset;
+use reqaz::watch::FileWatcher;
+use std::collections::{HashMap, HashSet};
+use std::fs::{create_dir_all, read, remove_file, write};
+use std::path::{absolute, Path, PathBuf};
+use std::process::ExitCode;
+use std::thread::available_parallelism;
+use std::time::Instant;
+use tokio::sync::mpsc::unbounded_channel;
+use tokio::task::{spawn_blocking, JoinSet};
+
+
+/// How a build job finished.
+enum JobOutcome {
+    /// The output was built from these inputs.
+    Built(CachedOutput),
+
+    /// The output was up to date, so it was left alone.
+    Unchanged
+}
+
+/// Settings applied to every output in a build.
+pub struct BuildOptions {
+    /// How to compress outputs, if at all.
+    pub compression: Option<Compression>,
+
+    /// Fingerprinted asset names to rewrite references to, if any.
+    pub fingerprints: Option<Fingerprints>
+}
+
+/// A single output to build.
+#[derive(Clone)]
+pub enum BuildJob {
+    /// Copy an asset.
+    Asset(AssetCopy),
+
+    /// Run a pipeline.
+    Pipeline(PipelineConfig)
+}
+
+#[expect(clippy::pattern_type_mismatch, reason = "jobs are matched by reference to borrow their configs")]
+impl BuildJob {
+    /// The asset path relative to root, if the job copies an asset.
+    pub fn asset_path(&self) -> Option<&Path> {
+        match self {
+            Self::Pipeline(_) => None,
+            Self::Asset(asset_copy) => Some(&asset_copy.asset.path)
+        }
+    }
+
+    /// Build the output into the output directory, along with any
+    /// compressed copies, returning what it was built from.
+    fn build(
+        &self,
+        resolver: &SourceResolver,
+        output_dir: &Path,
+        options: &BuildOptions
+    ) -> Result<CachedOutput> {
+        let out_path = output_dir.join(self.output());
+        let (body, mut cached) = self.render(resolver, options.fingerprints.as_ref())?;
+
+        create_dir_all(out_path.parent().unwrap_or(output_dir))?;
+
+        // Copies that aren't written anymore would be stale
+        for encoding in Encoding::ALL {
+            let compressed = match options.compression.as_ref() {
+                Some(settings) if settings.encodings.contains(&encoding) => {
+                    settings.compress(encoding, &body)?
+                },
+                Some(_) | None => None
+            };
+
+            let compressed_path = compressed_path(&out_path, encoding);
+
+            match compressed {
+                Some(compressed_body) => write(compressed_path, compressed_body)?,
+                None if compressed_path.is_file() => remove_file(compressed_path)?,
+                None => {}
+            }
+        }
+
+        write(out_path, body)?;
+
+        cached.mods = self.build_key(resolver, options, &cached.references);
+
+        Ok(cached)
+    }
+
+    /// A description of everything the output is built with besides
+    /// its files, so changing any of it rebuilds the output.
+    /// `references` are the paths it referenced when it was last
+    /// built, whose fingerprints it depends on.
+    fn build_key(&self, resolver: &SourceResolver, options: &BuildOptions, references: &BTreeSet<String>) -> String {
+        let mut key = self.mods_key(resolver);
+
+        if let Some(compression) = options.compression.as_ref() {
+            let encodings = compression.encodings
+                .iter()
+                .map(|encoding| encoding.name())
+                .collect::<Vec<_>>()
+                .join(",");
+
+            let quality = compression.quality
+                .map_or_else(|| "max".to_owned(), |quality| quality.to_string());
+
+            key = format!("{key}|{encodings}@{quality}>{}", compression.min_size);
+        }
+
+        if let Some(fingerprints) = options.fingerprints.as_ref() {
+            key = format!("{key}|fingerprint:{}", fingerprints.key(references));
+        }
+
+        key
+    }
+
+    /// The pipeline input URI or asset path, for reporting.
+    pub fn input(&self) -> String {
+        match self {
+            Self::Pipeline(pipeline) => pipeline.input.to_string(),
+            Self::Asset(asset_copy) => asset_copy.asset.path.display().to_string()
+        }
+    }
+
+    /// A description of the mods the output is built with, so
+    /// changing them rebuilds it. Plain copies don't run mods.
+    fn mods_key(&self, resolver: &SourceResolver) -> String {
+        if self.source_uri().is_none() {
+            return String::new();
+        }
+
+        let dir_keys = resolver.mods.dirs
+            .iter()
+            .map(|(dir, mods)| format!("{}={}", dir.display(), mods.join(",")));
+
+        once(resolver.mods.default.join(","))
+            .chain(dir_keys)
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
+    /// The output path, relative to the output directory.
+    pub fn output(&self) -> &Path {
+        match self {
+            Self::Pipeline(pipeline) => &pipeline.output,
+            Self::Asset(asset_copy) => &asset_copy.output
+        }
+    }
+
+    /// Build the output in memory, returning its contents and what
+    /// it was built from. References to fingerprinted assets are
+    /// rewritten.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the input can't be resolved or read.
+    pub fn render(
+        &self,
+        resolver: &SourceResolver,
+        fingerprints: Option<&Fingerprints>
+    ) -> Result<(Vec<u8>, CachedOutput)> {
+        let Some(uri) = self.source_uri() else {
+            let src_path = self.source_path(resolver);
+            let mime = src_path.get_media_type()
+                .unwrap_or(APPLICATION_OCTET_STREAM);
+
+            let mut body = read(&src_path)?;
+            let mut cached = CachedOutput::new(
+                self.input(),
+                self.mods_key(resolver),
+                mime.to_string(),
+                &BTreeSet::from([src_path]),
+                BTreeSet::new()
+            );
+
+            if let Some(names) = fingerprints {
+                (body, cached.references) = names.rewrite(body, &cached.mime, self.output());
+            }
+
+            return Ok((body, cached));
+        };
+
+        let source = resolver.resolve_source(uri)?;
+        let mut cached = CachedOutput::new(
+            self.input(),
+            self.mods_key(resolver),
+            source.mime.to_string(),
+            &source.dependencies,
+            source.remote
+        );
+
+        let mut body = source.body;
+
+        if let Some(names) = fingerprints {
+            (body, cached.references) = names.rewrite(body, &cached.mime, self.output());
+        }
+
+        Ok((body, cached))
+    }
+
+    /// The resolver to build the output with, running the
+    /// pipeline's own mods if it has any. They replace the
+    /// root-level mods, and win over a `dir_mods` entry the page is
+    /// under, while components under other folders keep theirs.
+    pub fn resolver_for(&self, resolver: &SourceResolver) -> SourceResolver {
+        match self {
+            Self::Pipeline(PipelineConfig { mods: Some(mods), input, .. }) => {
+                let page = PathBuf::from(input.path().trim_start_matches('/'));
+
+                let mut chains = resolver.mods.clone();
+                chains.default.clone_from(mods);
+
+                resolver.clone().with_mods(chains.with_dir(page, mods.clone()))
+            },
+            Self::Pipeline(_) | Self::Asset(_) => resolver.clone()
+        }
+    }
+
+    /// Change where the output is written, relative to the output directory.
+    pub fn set_output(&mut self, output: PathBuf) {
+        match self {
+            Self::Pipeline(pipeline) => pipeline.output = output,
+            Self::Asset(asset_copy) => asset_copy.output = output
+        }
+    }
+
+    /// The file under root the output is built from. Pipeline
+    /// inputs leading outside of root have none, and fail to build.
+    pub fn source_path(&self, resolver: &SourceResolver) -> PathBuf {
+        match self {
+            Self::Pipeline(pipeline) => resolver.get_path_from_uri(&pipeline.input).unwrap_or_default(),
+            Self::Asset(asset_copy) => resolver.root.join(&asset_copy.asset.path)
+        }
+    }
+
+    /// The URI to resolve to build the output, unless it's
+    /// a plain copy.
+    pub fn source_uri(&self) -> Option<&Uri> {
+        match self {
+            Self::Pipeline(pipeline) => Some(&pipeline.input),
+            Self::Asset(asset_copy) if is_scss(&asset_copy.asset.path) => {
+                Some(&asset_copy.asset.uri)
+            },
+            Self::Asset(_) => None
+        }
+    }
+}
+
+/// An asset to copy, and where to.
+#[derive(Clone)]
+pub struct AssetCopy {
+    /// The asset under root.
+    asset: Asset,
+
+    /// The output path, relative to the output directory.
+    output: PathBuf
+}
+
+/// Run every pipeline and copy assets, writing results into the
+/// output directory.
+///
+/// Outputs are built in parallel, at most `generate.jobs` at a time.
+/// Outputs whose inputs haven't changed since the last build, going
+/// by the cache next to the output directory, are left alone.
+/// Building stops at the first failure unless `keep_going` is set.
+/// Either way, outputs of previous builds that no pipeline produces
+/// anymore are removed, a manifest of every output is written into
+/// the output directory, a report is printed in pipeline order, and
+/// the exit code is non-zero if anything failed.
+pub async fn run(
+    resolver: &SourceResolver,
+    generate: &GenerateConfig,
+    keep_going: bool
+) -> Result<ExitCode> {
+    let (jobs, fingerprints) = {
+        let plan_resolver = resolver.clone();
+        let plan_generate = generate.clone();
+
+        spawn_blocking(move || plan_jobs(&plan_resolver, &plan_generate)).await??
+    };
+
+    let job_count = generate.jobs
+        .unwrap_or_else(|| available_parallelism().map_or(1, NonZeroUsize::get))
+        .max(1);
+
+    let cache_dir = BuildCache::dir_for(&generate.output_dir);
+    let shared_cache = Arc::new(BuildCache::load(&cache_dir));
+    let previous = Manifest::load(&generate.output_dir);
+    let options = Arc::new(BuildOptions {
+        compression: generate.compress.as_ref().map(CompressConfig::compression),
+        fingerprints
+    });
+
+    let mut outcomes: Vec<Option<Result<JobOutcome>>> = jobs.iter().map(|_| None).collect();
+    let mut queue = jobs.iter().cloned().enumerate();
+    let mut running = JoinSet::new();
+    let mut running_jobs = HashMap::new();
+    let mut stopped = false;
+
+    loop {
+        while !stopped && running.len() < job_count {
+            let Some((idx, job)) = queue.next() else {
+                break;
+            };
+
+            let job_resolver = job.resolver_for(resolver);
+            let output_dir = generate.output_dir.clone();
+            let job_cache = Arc::clone(&shared_cache);
+            let job_options = Arc::clone(&options);
+
+            let task = running.spawn_blocking(move || {
+                let references = job_cache.get(job.output())
+                    .map(|cached| cached.references.clone())
+                    .unwrap_or_default();
+                let key = job.build_key(&job_resolver, &job_options, &references);
+
+                if job_cache.is_fresh(&job.input(), job.output(), &output_dir, &key) {
+                    Ok(JobOutcome::Unchanged)
+                } else {
+                    job.build(&job_resolver, &output_dir, &job_options)
+                        .map(JobOutcome::Built)
+                }
+            });
+
+            running_jobs.insert(task.id(), idx);
+        }
+
+        let Some(joined) = running.join_next_with_id().await else {
+            break;
+        };
+
+        // A task that panicked fails its own job, like an error would
+        let (task_id, outcome) = match joined {
+            Ok((task_id, outcome)) => (task_id, outcome),
+            Err(error) => (error.id(), Err(error.into()))
+        };
+
+        let Some(idx) = running_jobs.remove(&task_id) else {
+            continue;
+        };
+
+        if outcome.is_err() && !keep_going {
+            stopped = true;
+        }
+
+        if let Some(slot) = outcomes.get_mut(idx) {
+            *slot = Some(outcome);
+        }
+    }
+
+    // Every task has finished, so this is the only reference left
+    let mut cache = Arc::into_inner(shared_cache).unwrap_or_default();
+    let mut report = BuildReport { planned: jobs.len(), ..BuildReport::default() };
+
+    for (job, outcome) in jobs.iter().zip(outcomes) {
+        let output = job.output().to_path_buf();
+
+        match outcome {
+            Some(Ok(JobOutcome::Unchanged)) => report.record_unchanged(output),
+            Some(Ok(JobOutcome::Built(cached))) => {
+                cache.insert(output.clone(), cached);
+                report.record(job.input(), output, Ok(()));
+            },
+            Some(Err(error)) => {
+                cache.remove(&output);
+                report.record(job.input(), output, Err(error));
+            },
+            None => {}
+        }
+    }
+
+    cache.retain(&jobs.iter().map(BuildJob::output).collect());
+    cache.save(&cache_dir)?;
+
+    for output in prune(&generate.output_dir, &previous.stale(&jobs))? {
+        report.record_pruned(output);
+    }
+
+    create_dir_all(&generate.output_dir)?;
+    Manifest::from_cache(resolver, &jobs, &cache, &previous, &generate.output_dir)
+        .save(&generate.output_dir)?;
+
+    report.print();
+
+    Ok(report.exit_code())
+}
+
+/// Build once, then keep watching root and rebuild whenever
+/// something changes, printing a report and how long each build
+/// took. Only outputs affected by the change are rebuilt, going by
+/// the build cache. Runs until the process is stopped.
+///
+/// # Errors
+///
+/// Errors if root can't be watched.
+#[expect(clippy::print_stdout, reason = "what's being watched and what changed is the command's output")]
+#[expect(clippy::single_call_fn, reason = "the `build --watch` entry point")]
+pub async fn watch(
+    resolver: &SourceResolver,
+    generate: &GenerateConfig,
+    keep_going: bool
+) -> Result<ExitCode> {
+    timed_run(resolver, generate, keep_going).await;
+
+    let written = written_dirs(&generate.output_dir)?;
+    let (sender, mut receiver) = unbounded_channel();
+
+    let _watcher = FileWatcher::new(&resolver.root, move |paths| {
+        let changed: Vec<PathBuf> = paths.into_iter()
+            .filter(|path| !is_written(path, &written))
+            .collect();
+
+        if !changed.is_empty() {
+            #[expect(clippy::unused_result_ok, reason = "the receiver only stops with the process")]
+            sender.send(changed).ok();
+        }
+    })?;
+
+    println!("Watching {} for changes...", resolver.root.display());
+
+    while let Some(mut changed) = receiver.recv().await {
+        // Fold in anything that came in while the last build ran
+        while let Ok(more) = receiver.try_recv() {
+            changed.extend(more);
+        }
+
+        println!();
+        println!("{} files changed, rebuilding...", changed.len());
+
+        timed_run(resolver, generate, keep_going).await;
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// The directories a build writes to, the output directory and
+/// the cache next to it, as the watcher reports them.
+///
+/// # Errors
+///
+/// Errors if the current directory can't be found.
+#[cfg_attr(not(test), expect(clippy::single_call_fn, reason = "split from `watch` so it can be tested"))]
+fn written_dirs(output_dir: &Path) -> Result<[PathBuf; 2]> {
+    let absolute_dir = absolute(output_dir)?;
+    let canonical_dir = absolute_dir.canonicalize().unwrap_or(absolute_dir);
+
+    Ok([BuildCache::dir_for(&canonical_dir), canonical_dir])
+}
+
+/// Whether a changed path is one a build wrote, so watching
+/// doesn't trigger another build for it.
+#[cfg_attr(not(test), expect(clippy::single_call_fn, reason = "split from `watch` so it can be tested"))]
+fn is_written(path: &Path, written: &[PathBuf]) -> bool {
+    written.iter().any(|dir| path.starts_with(dir))
+}
+
+/// Build once, printing how long it took. Errors are printed
+/// rather than returned, so watching carries on.
+#[expect(clippy::print_stderr, reason = "a failed build is reported and watching carries on")]
+#[expect(clippy::print_stdout, reason = "build timings are part of watch's output")]
+#[expect(clippy::use_debug, reason = "`Duration` only formats with units through `Debug`")]
+async fn timed_run(resolver: &SourceResolver, generate: &GenerateConfig, keep_going: bool) {
+    let start = Instant::now();
+
+    if let Err(err) = run(resolver, generate, keep_going).await {
+        eprintln!("{} {err:#}", "error:".red().bold());
+    }
+
+    println!("Finished in {:.2?}.", start.elapsed());
+}
+
+/// Where the copy of an output compressed with an encoding goes,
+/// like `index.html.gz`.
+pub fn compressed_path(output: &Path, encoding: Encoding) -> PathBuf {
+    let mut path = output.as_os_str().to_owned();
+
+    path.push(".");
+    path.push(encoding.extension());
+
+    PathBuf::from(path)
+}
+
+/// Collect every output to build, giving assets fingerprinted
+/// names if configured.
+///
+/// # Errors
+///
+/// Errors if outputs can't be collected, or fingerprinting fails.
+pub fn plan_jobs(
+    resolver: &SourceResolver,
+    generate: &GenerateConfig
+) -> Result<(Vec<BuildJob>, Option<Fingerprints>)> {
+    let mut jobs = collect_jobs(resolver, generate)?;
+
+    let fingerprints = match generate.fingerprint.as_ref() {
+        Some(fingerprint_config) => Some(Fingerprints::assign(resolver, &mut jobs, fingerprint_config)?),
+        None => None
+    };
+
+    Ok((jobs, fingerprints))
+}
+
+/// Collect every output to build, pipelines first.
+pub fn collect_jobs(resolver: &SourceResolver, generate: &GenerateConfig) -> Result<Vec<BuildJob>> {
+    let pipelines = collect_pipelines(resolver, generate)?;
+
+    let assets = match generate.assets.as_ref() {
+        Some(assets_config) => collect_assets(resolver, generate, assets_config)?,
+        None => vec![]
+    };
+
+    Ok(pipelines.into_iter()
+        .map(BuildJob::Pipeline)
+        .chain(assets.into_iter().map(BuildJob::Asset))
+        .collect())
+}
+
+/// Collect every pipeline to run. Explicit pipelines come first,
+/// followed by discovered pages whose output they don't already
+/// cover.
+#[expect(clippy::single_call_fn, reason = "`collect_jobs` reads better with pipelines and assets collected apart")]
+pub fn collect_pipelines(
+    resolver: &SourceResolver,
+    generate: &GenerateConfig
+) -> Result<Vec<PipelineConfig>> {
+    let mut pipelines = generate.pipelines.clone();
+
+    let Some(pages) = generate.pages.as_ref() else {
+        return Ok(pipelines);
+    };
+
+    let globs = pages.globs();
+    let include = glob_set(&globs.include)?;
+    let exclude = glob_set(&globs.exclude)?;
+
+    let output_dir = absolute(&generate.output_dir)?;
+
+    let mut outputs: HashSet<PathBuf> = pipelines.iter()
+        .map(|pipeline| pipeline.output.clone())
+        .collect();
+
+    for page in resolver.discover_pages()? {
+        if is_built(resolver, &page.path, &output_dir)
+            || !include.is_match(&page.path)
+            || exclude.is_match(&page.path)
+            || !outputs.insert(page.path.clone())
+        {
+            continue;
+        }
+
+        pipelines.push(PipelineConfig {
+            input: page.uri,
+            output: page.path,
+            mods: None
+        });
+    }
+
+    Ok(pipelines)
+}
+
+/// Collect every asset to copy. SCSS files are written as CSS.
+#[expect(clippy::single_call_fn, reason = "asset collection is long enough to stand on its own")]
+fn collect_assets(
+    resolver: &SourceResolver,
+    generate: &GenerateConfig,
+    assets: &AssetsConfig
+) -> Result<Vec<AssetCopy>> {
+    let ignore = glob_set(&assets.ignore)?;
+    let output_dir = absolute(&generate.output_dir)?;
+
+    Ok(resolver.discover_assets()?
+        .into_iter()
+        .filter(|asset| !ignore.is_match(&asset.path) && !is_built(resolver, &asset.path, &output_dir))
+        .map(|asset| {
+            let output = if is_scss(&asset.path) {
+                asset.path.with_extension("css")
+            } else {
+                asset.path.clone()
+            };
+
+            AssetCopy { asset, output }
+        })
+        .collect())
+}
+
+/// Whether a path is an SCSS file.
+fn is_scss(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("scss"))
+}
+
+/// Whether a path under root is inside the output directory, so
+/// a previous build isn't fed back into the next one.
+fn is_built(resolver: &SourceResolver, path: &Path, output_dir: &Path) -> bool {
+    absolute(resolver.root.join(path))
+        .is_ok_and(|full_path| full_path.starts_with(output_dir))
+}
+
+/// Build a glob set from a list of globs.
+pub fn glob_set(globs: &[String]) -> Result<GlobSet, globset::Error> {
+    let mut builder = GlobSetBuilder::new();
+
+    for glob in globs {
+        builder.add(Glob::new(glob)?);
+    }
+
+    builder.build()
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used, reason = "a failed unwrap fails the test")]
+mod tests {
+    use super::{is_written, written_dirs, BuildJob};
+    use crate::cli::config::PipelineConfig;
+    use http::uri::Uri;
+    use reqaz::html::ModChains;
+    use reqaz::source::SourceResolver;
+    use std::env::temp_dir;
+    use std::fs::create_dir_all;
+    use std::path::PathBuf;
+
+    fn mods(names: &[&str]) -> Vec<String> {
+        names.iter().map(|&name| name.to_owned()).collect()
+    }
+
+    #[test]
+    fn pipeline_mods_keep_dir_mods() {
+        let resolver = SourceResolver::new(PathBuf::from("web"), "reqaz.local".try_into().unwrap())
+            .with_mods(
+                ModChains::new(mods(&["fetch", "css", "component"]))
+                    .with_dir(PathBuf::from("_components"), mods(&["component"]))
+                    .with_dir(PathBuf::from("emails"), mods(&["fetch"]))
+            );
+
+        let job = BuildJob::Pipeline(PipelineConfig {
+            input: Uri::from_static("/emails/welcome.html"),
+            output: PathBuf::from("welcome.html"),
+            mods: Some(mods(&["query", "component"]))
+        });
+
+        let chains = job.resolver_for(&resolver).mods;
+
+        assert_eq!(chains.for_uri(&Uri::from_static("/emails/welcome.html")), mods(&["query", "component"]));
+        assert_eq!(chains.for_uri(&Uri::from_static("/emails/other.html")), mods(&["fetch"]));
+        assert_eq!(chains.for_uri(&Uri::from_static("/_components/nav.html")), mods(&["component"]));
+        assert_eq!(chains.for_uri(&Uri::from_static("/about.html")), mods(&["query", "component"]));
+    }
+
+    #[test]
+    fn watch_ignores_written_dirs() {
+        let site_dir = temp_dir().join("reqaz_watch_site");
+        create_dir_all(site_dir.join("web")).unwrap();
+        create_dir_all(site_dir.join("out")).unwrap();
+
+        let site = site_dir.canonicalize().unwrap();
+        let written = written_dirs(&site.join("out")).unwrap();
+
+        assert!(is_written(&site.join("out/index.html"), &written));
+        assert!(is_written(&site.join("out/blog/post.html"), &written));
+        assert!(is_written(&site.join(".out.cache/graph.json"), &written));
+        assert!(!is_written(&site.join("web/index.html"), &written));
+        assert!(!is_written(&site.join("outline.html"), &written));
+    }
+}
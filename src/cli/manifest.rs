@@ -0,0 +1,185 @@
+extern crate alloc;
+
+use super::cache::{content_hash, BuildCache};
+use super::generate::{compressed_path, BuildJob};
+use alloc::collections::BTreeSet;
+use color_eyre::Result;
+use reqaz::compress::Encoding;
+use reqaz::source::SourceResolver;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{metadata, read_to_string, write};
+use std::path::{Component, Path, PathBuf};
+
+/// The file the manifest is written to, inside the output directory.
+pub const MANIFEST_FILE: &str = "reqaz-manifest.json";
+
+/// A machine-readable record of everything a build produced.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Manifest {
+    /// Every output in the output directory, in pipeline order.
+    pub outputs: Vec<ManifestEntry>
+}
+
+/// A single output in the manifest.
+#[derive(Serialize, Deserialize, Clone)]
+#[expect(clippy::module_name_repetitions, reason = "reads better than `manifest::Entry` where it's used")]
+#[expect(clippy::arbitrary_source_item_ordering, reason = "fields are written to the JSON in this order")]
+pub struct ManifestEntry {
+    /// The output path, relative to the output directory.
+    pub output: PathBuf,
+
+    /// The pipeline input URI or asset path.
+    pub input: String,
+
+    /// The size of the output in bytes.
+    pub size: u64,
+
+    /// The hex SHA-256 of the output.
+    pub hash: String,
+
+    /// The MIME type of the output.
+    pub mime: String,
+
+    /// HTML files pulled into the output, relative to root.
+    pub components: Vec<PathBuf>,
+
+    /// CSS and SCSS files pulled into the output, relative to root.
+    pub stylesheets: Vec<PathBuf>,
+
+    /// Remote URLs fetched to build the output.
+    pub remote: Vec<String>,
+
+    /// Compressed copies of the output, relative to the output directory.
+    #[serde(default)]
+    pub compressed: Vec<PathBuf>
+}
+
+impl Manifest {
+    /// Every output in the manifest.
+    pub fn all(&self) -> Vec<PathBuf> {
+        self.outputs.iter()
+            .map(|entry| entry.output.clone())
+            .filter(|output| is_contained(output))
+            .collect()
+    }
+
+    /// Describe every job's output, using what the cache recorded
+    /// it was built from. Outputs that failed keep their entry from
+    /// the previous manifest while the old file is still around, so
+    /// it can still be pruned later. Outputs that were never built
+    /// are left out.
+    #[expect(clippy::single_call_fn, reason = "kept apart from the build loop that calls it")]
+    pub fn from_cache(
+        resolver: &SourceResolver,
+        jobs: &[BuildJob],
+        cache: &BuildCache,
+        previous: &Self,
+        output_dir: &Path
+    ) -> Self {
+        let outputs = jobs.iter()
+            .filter_map(|job| {
+                let out_path = output_dir.join(job.output());
+                let size = metadata(&out_path).ok()?.len();
+
+                let Some(cached) = cache.get(job.output()) else {
+                    let entry = previous.get(job.output())?;
+
+                    return Some(ManifestEntry {
+                        size,
+                        hash: content_hash(&out_path),
+                        compressed: compressed_copies(job.output(), output_dir),
+                        ..entry.clone()
+                    });
+                };
+
+                let source = job.source_path(resolver);
+
+                // Missing files are recorded so creating them triggers
+                // a rebuild, but weren't actually pulled in
+                let dependencies: BTreeSet<&Path> = cached.dependencies
+                    .iter()
+                    .filter(|&(path, hash)| *path != source && !hash.is_empty())
+                    .map(|(path, _)| path.strip_prefix(&resolver.root).unwrap_or(path))
+                    .collect();
+
+                Some(ManifestEntry {
+                    output: job.output().to_path_buf(),
+                    input: job.input(),
+                    size,
+                    hash: content_hash(&out_path),
+                    mime: cached.mime.clone(),
+                    components: with_extensions(&dependencies, &["html"]),
+                    stylesheets: with_extensions(&dependencies, &["css", "scss"]),
+                    remote: cached.remote.iter().cloned().collect(),
+                    compressed: compressed_copies(job.output(), output_dir)
+                })
+            })
+            .collect();
+
+        Self { outputs }
+    }
+
+    /// The entry for an output.
+    pub fn get(&self, output: &Path) -> Option<&ManifestEntry> {
+        self.outputs.iter().find(|entry| entry.output == output)
+    }
+
+    /// Read the manifest from an output directory. A missing or
+    /// unreadable manifest is treated as empty.
+    pub fn load(output_dir: &Path) -> Self {
+        read_to_string(output_dir.join(MANIFEST_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the manifest into an output directory.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the output directory can't be written to.
+    pub fn save(&self, output_dir: &Path) -> Result<()> {
+        write(output_dir.join(MANIFEST_FILE), serde_json::to_vec_pretty(self)?)?;
+
+        Ok(())
+    }
+
+    /// Every output in the manifest that none of the jobs build
+    /// anymore.
+    pub fn stale(&self, jobs: &[BuildJob]) -> Vec<PathBuf> {
+        let current: HashSet<&Path> = jobs.iter().map(BuildJob::output).collect();
+
+        self.outputs.iter()
+            .map(|entry| entry.output.clone())
+            .filter(|output| !current.contains(output.as_path()) && is_contained(output))
+            .collect()
+    }
+}
+
+/// Every compressed copy of an output that exists.
+fn compressed_copies(output: &Path, output_dir: &Path) -> Vec<PathBuf> {
+    Encoding::ALL
+        .into_iter()
+        .map(|encoding| compressed_path(output, encoding))
+        .filter(|path| output_dir.join(path).is_file())
+        .collect()
+}
+
+/// Whether a path stays inside the directory it's joined onto.
+/// Outputs that don't are never listed, so a hand-edited manifest
+/// can't get files outside the output directory removed.
+fn is_contained(path: &Path) -> bool {
+    path.components().all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Every path with one of the given extensions.
+fn with_extensions(paths: &BTreeSet<&Path>, extensions: &[&str]) -> Vec<PathBuf> {
+    paths.iter()
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| extensions.iter().any(|wanted| ext.eq_ignore_ascii_case(wanted)))
+        })
+        .map(|path| path.to_path_buf())
+        .collect()
+}
@@ -0,0 +1,32 @@
+use super::config::CliConfig;
+use super::serve::listen;
+use color_eyre::owo_colors::OwoColorize as _;
+use color_eyre::Result;
+use http::uri::Authority;
+use reqaz::source::{SourceResolver, SourceService};
+use std::path::Path;
+
+
+/// Serve the output directory as plain static files, exactly as
+/// they'd be deployed, until the process is stopped.
+///
+/// # Errors
+///
+/// Errors if the address can't be bound.
+#[expect(clippy::print_stdout, reason = "the preview address is the command's output")]
+#[expect(clippy::single_call_fn, reason = "the `preview` subcommand's entry point")]
+pub async fn run(output_dir: &Path, authority: Authority, config: &CliConfig) -> Result<()> {
+    let resolver = SourceResolver::new(output_dir.to_path_buf(), authority)
+        .with_raw()
+        .with_follow_symlinks(config.follow_symlinks);
+    let service = SourceService::new(resolver, config.log)
+        .with_cache_control(config.cache_control()?);
+
+    println!(
+        "Previewing {} at {}",
+        output_dir.display(),
+        format!("http://{}:{}/", config.host, config.port).bold()
+    );
+
+    listen(service, config).await
+}
@@ -0,0 +1,132 @@
+use color_eyre::owo_colors::OwoColorize as _;
+use eyre::Report;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+
+/// The outcome of every pipeline and asset in a build.
+#[derive(Default)]
+#[expect(clippy::module_name_repetitions, reason = "a bare `Report` would clash with `eyre::Report`")]
+pub struct BuildReport {
+    /// Everything that failed, in build order.
+    pub failures: Vec<BuildFailure>,
+
+    /// How many outputs the build set out to write.
+    pub planned: usize,
+
+    /// Outputs of previous builds that were removed, relative to
+    /// the output directory.
+    pub pruned: Vec<PathBuf>,
+
+    /// Outputs written successfully, relative to the output directory.
+    pub succeeded: Vec<PathBuf>,
+
+    /// Outputs that were already up to date, relative to the output directory.
+    pub unchanged: Vec<PathBuf>
+}
+
+/// A pipeline or asset that failed to build.
+pub struct BuildFailure {
+    /// What went wrong.
+    pub error: Report,
+
+    /// The input URI or asset path.
+    pub input: String,
+
+    /// The output path, relative to the output directory.
+    pub output: PathBuf
+}
+
+impl BuildReport {
+    /// The process exit code for the build, non-zero if anything failed.
+    pub const fn exit_code(&self) -> ExitCode {
+        if self.has_failures() {
+            ExitCode::FAILURE
+        } else {
+            ExitCode::SUCCESS
+        }
+    }
+
+    /// Whether anything failed so far.
+    pub const fn has_failures(&self) -> bool {
+        !self.failures.is_empty()
+    }
+
+    /// Print every failure with its error chain, followed by a summary.
+    #[expect(clippy::print_stderr, reason = "failures are reported like any other error")]
+    #[expect(clippy::print_stdout, reason = "the summary is what a build prints when it's done")]
+    pub fn print(&self) {
+        for failure in &self.failures {
+            eprintln!(
+                "{} {} -> {}",
+                "error:".red().bold(),
+                failure.input,
+                failure.output.display()
+            );
+
+            for (idx, cause) in failure.error.chain().enumerate() {
+                if idx == 0 {
+                    eprintln!("    {cause}");
+                } else {
+                    eprintln!("    caused by: {cause}");
+                }
+            }
+        }
+
+        let built = self.succeeded.len();
+        let failed = self.failures.len();
+        let unchanged = self.unchanged.len();
+        let skipped = self.planned
+            .saturating_sub(built.saturating_add(failed).saturating_add(unchanged));
+
+        let up_to_date = if unchanged > 0 {
+            format!(", {unchanged} up to date")
+        } else {
+            String::new()
+        };
+
+        let stale = if self.pruned.is_empty() {
+            String::new()
+        } else {
+            format!(", {} stale removed", self.pruned.len())
+        };
+
+        if skipped > 0 {
+            println!(
+                "Built {built} of {} outputs, {} failed{up_to_date}{stale}, {skipped} skipped (use --keep-going to build the rest).",
+                self.planned,
+                failed.red().bold()
+            );
+        } else if failed > 0 {
+            println!(
+                "Built {built} of {} outputs, {} failed{up_to_date}{stale}.",
+                self.planned,
+                failed.red().bold()
+            );
+        } else {
+            println!("Built {built} outputs{up_to_date}{stale}.");
+        }
+    }
+
+    /// Record the outcome of building one output.
+    pub fn record(&mut self, input: String, output: PathBuf, outcome: Result<(), Report>) {
+        match outcome {
+            Ok(()) => self.succeeded.push(output),
+            Err(error) => self.failures.push(BuildFailure {
+                error,
+                input,
+                output
+            })
+        }
+    }
+
+    /// Record a stale output that was removed.
+    pub fn record_pruned(&mut self, output: PathBuf) {
+        self.pruned.push(output);
+    }
+
+    /// Record an output that was already up to date.
+    pub fn record_unchanged(&mut self, output: PathBuf) {
+        self.unchanged.push(output);
+    }
+}
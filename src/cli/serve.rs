@@ -0,0 +1,102 @@
+use super::config::{CliConfig, Protocol};
+use color_eyre::Result;
+use core::error::Error;
+use core::net::SocketAddr;
+use hyper::server::conn::{http1, http2};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use reqaz::reload::LiveReload;
+use reqaz::source::{SourceResolver, SourceService};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::spawn as tokio_spawn;
+
+
+/// The error type of a served connection.
+type ConnectionError = Box<dyn Error + Send + Sync>;
+
+/// Serve files in root on the configured host and port until
+/// the process is stopped.
+///
+/// # Errors
+///
+/// Errors if the address can't be bound or root can't be watched.
+#[expect(clippy::single_call_fn, reason = "the `serve` subcommand's entry point")]
+pub async fn run(resolver: SourceResolver, config: &CliConfig) -> Result<()> {
+    let service = {
+        let mut base_service = SourceService::new(
+            resolver.clone(),
+            config.log
+        )
+            .with_error_overlay()
+            .with_cache_control(config.cache_control()?);
+
+        // Serve what the CDN would, if outputs are precompressed
+        if let Some(compress) = config.generate.as_ref().and_then(|generate| generate.compress.as_ref()) {
+            base_service = base_service.with_compression(compress.compression());
+        }
+
+        if let Some(proxy) = config.proxy()? {
+            base_service = base_service.with_proxy(proxy);
+        }
+
+        if config.live_reload {
+            base_service.with_live_reload(LiveReload::watch(resolver)?)
+        } else {
+            base_service
+        }
+    };
+
+    listen(service, config).await
+}
+
+/// Serve a service on the configured host and port until the
+/// process is stopped.
+///
+/// # Errors
+///
+/// Errors if the address can't be bound.
+pub async fn listen(service: SourceService, config: &CliConfig) -> Result<()> {
+    let addr = SocketAddr::new(config.host, config.port);
+    let listener = TcpListener::bind(addr).await?;
+    let protocol = config.protocol;
+
+    #[expect(clippy::infinite_loop, reason = "serving carries on until the process is stopped")]
+    loop {
+        let accepted = listener.accept().await;
+
+        if let Ok((stream, _)) = accepted {
+            let service_clone = service.clone();
+
+            #[expect(clippy::print_stderr, reason = "a failed connection is only worth a log line")]
+            tokio_spawn(async move {
+                if let Err(err) = serve_connection(stream, service_clone, protocol).await {
+                    eprintln!("Error serving request: {err}");
+                }
+            });
+        }
+    }
+}
+
+/// Serve a single connection with the given protocol.
+#[expect(clippy::single_call_fn, reason = "keeps the accept loop short")]
+async fn serve_connection(
+    stream: TcpStream,
+    service: SourceService,
+    protocol: Protocol
+) -> Result<(), ConnectionError> {
+    let io = TokioIo::new(stream);
+
+    match protocol {
+        Protocol::Http1 => http1::Builder::new()
+            .serve_connection(io, service)
+            .await
+            .map_err(Into::into),
+        Protocol::Http2 => http2::Builder::new(TokioExecutor::new())
+            .serve_connection(io, service)
+            .await
+            .map_err(Into::into),
+        Protocol::Auto => auto::Builder::new(TokioExecutor::new())
+            .serve_connection(io, service)
+            .await
+    }
+}
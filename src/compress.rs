@@ -0,0 +1,215 @@
+use brotli::enc::BrotliEncoderParams;
+use flate2::write::GzEncoder;
+use flate2::Compression as GzLevel;
+use std::io::{Result as IoResult, Write as _};
+
+
+/// The highest gzip quality.
+const GZIP_MAX_QUALITY: u32 = 9;
+
+/// The highest brotli quality.
+const BROTLI_MAX_QUALITY: u32 = 11;
+
+/// The highest gzip quality used while a response waits.
+const GZIP_ON_THE_FLY_QUALITY: u32 = 6;
+
+/// The highest brotli quality used while a response waits. Past
+/// this, brotli gets much slower for little gain.
+const BROTLI_ON_THE_FLY_QUALITY: u32 = 4;
+
+/// The smallest body worth compressing by default, in bytes.
+const DEFAULT_MIN_SIZE: usize = 1024;
+
+/// A content encoding a body can be compressed with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// Brotli, as `br`.
+    Brotli,
+
+    /// Gzip, as `gzip`.
+    Gzip
+}
+
+impl Encoding {
+    /// Every encoding, in order of preference.
+    pub const ALL: [Self; 2] = [Self::Brotli, Self::Gzip];
+
+    /// Compress a body. Without a quality, or with one past what the
+    /// encoding supports, the highest quality is used.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the encoder fails.
+    #[inline]
+    pub fn compress(self, body: &[u8], quality: Option<u32>) -> IoResult<Vec<u8>> {
+        match self {
+            Self::Brotli => {
+                let params = BrotliEncoderParams {
+                    quality: i32::try_from(quality.map_or(BROTLI_MAX_QUALITY, |level| level.min(BROTLI_MAX_QUALITY)))
+                        .unwrap_or_default(),
+                    ..BrotliEncoderParams::default()
+                };
+
+                let mut compressed = vec![];
+                brotli::BrotliCompress(&mut &*body, &mut compressed, &params)?;
+
+                Ok(compressed)
+            },
+            Self::Gzip => {
+                let level = quality.map_or(GZIP_MAX_QUALITY, |level| level.min(GZIP_MAX_QUALITY));
+                let mut encoder = GzEncoder::new(vec![], GzLevel::new(level));
+
+                encoder.write_all(body)?;
+                encoder.finish()
+            }
+        }
+    }
+
+    /// The extension added to a file compressed with the encoding.
+    #[inline]
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Brotli => "br",
+            Self::Gzip => "gz"
+        }
+    }
+
+    /// The name of the encoding, as used in `Content-Encoding`.
+    #[inline]
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Brotli => "br",
+            Self::Gzip => "gzip"
+        }
+    }
+
+    /// The highest quality worth compressing with while a response
+    /// waits on the body.
+    #[inline]
+    #[must_use]
+    pub const fn on_the_fly_quality(self) -> u32 {
+        match self {
+            Self::Brotli => BROTLI_ON_THE_FLY_QUALITY,
+            Self::Gzip => GZIP_ON_THE_FLY_QUALITY
+        }
+    }
+}
+
+/// Which encodings to compress bodies with, and when.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Compression {
+    /// The encodings to use, in order of preference.
+    pub encodings: Vec<Encoding>,
+
+    /// Bodies smaller than this many bytes are left alone.
+    pub min_size: usize,
+
+    /// Whether bodies are compressed while responses wait on them,
+    /// capping the quality at each encoding's on-the-fly quality.
+    pub on_the_fly: bool,
+
+    /// The quality to compress with, highest if unset.
+    pub quality: Option<u32>
+}
+
+impl Compression {
+    /// Compress a body, unless it's too small or compressing
+    /// wouldn't make it any smaller.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the encoder fails.
+    #[inline]
+    pub fn compress(&self, encoding: Encoding, body: &[u8]) -> IoResult<Option<Vec<u8>>> {
+        if body.len() < self.min_size {
+            return Ok(None);
+        }
+
+        let quality = if self.on_the_fly {
+            let cap = encoding.on_the_fly_quality();
+            Some(self.quality.map_or(cap, |level| level.min(cap)))
+        } else {
+            self.quality
+        };
+
+        let compressed = encoding.compress(body, quality)?;
+
+        Ok((compressed.len() < body.len()).then_some(compressed))
+    }
+
+    /// Pick the most preferred encoding an `Accept-Encoding` header
+    /// allows, if any.
+    #[inline]
+    #[must_use]
+    pub fn negotiate(&self, accept_encoding: &str) -> Option<Encoding> {
+        let accepted: Vec<(&str, bool)> = accept_encoding
+            .split(',')
+            .map(|entry| {
+                let mut parts = entry.split(';').map(str::trim);
+                let name = parts.next().unwrap_or_default();
+
+                // Only an explicit zero quality refuses an encoding
+                let refused = parts
+                    .filter_map(|param| param.strip_prefix("q="))
+                    .any(|quality| quality.parse::<f32>().is_ok_and(|value| value <= 0.0));
+
+                (name, !refused)
+            })
+            .collect();
+
+        self.encodings.iter().copied().find(|encoding| {
+            accepted.iter()
+                .find(|&&(name, _)| name.eq_ignore_ascii_case(encoding.name()))
+                .or_else(|| accepted.iter().find(|&&(name, _)| name == "*"))
+                .is_some_and(|&(_, allowed)| allowed)
+        })
+    }
+
+    /// Compress with the given encodings, in order of preference.
+    #[inline]
+    #[must_use]
+    pub const fn new(encodings: Vec<Encoding>) -> Self {
+        Self {
+            encodings,
+            min_size: DEFAULT_MIN_SIZE,
+            on_the_fly: false,
+            quality: None
+        }
+    }
+
+    /// Compress bodies while responses wait on them, capping the
+    /// quality so compressing doesn't hold them up.
+    #[inline]
+    #[must_use]
+    pub const fn on_the_fly(mut self) -> Self {
+        self.on_the_fly = true;
+        self
+    }
+
+    /// Set the smallest body worth compressing, in bytes.
+    #[inline]
+    #[must_use]
+    pub const fn with_min_size(mut self, min_size: usize) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Set the quality to compress with.
+    #[inline]
+    #[must_use]
+    pub const fn with_quality(mut self, quality: Option<u32>) -> Self {
+        self.quality = quality;
+        self
+    }
+}
+
+impl Default for Compression {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Encoding::ALL.to_vec())
+    }
+}
@@ -1,35 +1,93 @@
-use self::mods::HtmlModManager;
-use crate::source::SourceResolver;
-use hyper::Uri;
-use kuchikiki::traits::TendrilSink;
+extern crate alloc;
 
 /// Utilities for HTML element attributes
-mod attr;
+pub mod attr;
 
 /// reqaz-builtin HTML mods
 pub(crate) mod mods;
 
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::cell::RefCell;
+use self::mods::HtmlModManager;
+use crate::source::SourceResolver;
+use hyper::Uri;
+use kuchikiki::traits::TendrilSink;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The mods run on every page unless configured otherwise.
+const DEFAULT_MODS: [&str; 4] = ["query", "fetch", "css", "component"];
+
 pub type Html = kuchikiki::NodeRef;
 
-/// Apply internal mods to a parsed HTML segment
-macro_rules! apply_internal_mods {
-    ($resolver:ident, $uri:ident, $dom:ident, [$($mod_name:literal),*]) => {
-        {
-            let mut mod_manager = HtmlModManager {
-                page_uri: $uri.clone(),
-                mod_cache: Default::default(),
-                resolver: $resolver.clone()
-            };
-
-            mod_manager.load_mods([$($mod_name),*]);
-
-            Ok($dom)
-                $(
-                    .and_then(|new_dom| mod_manager.apply_mod(new_dom, $mod_name))
-                )*
-                .map(|new_dom| new_dom.to_string())
+/// Which mods run on a page, and in what order.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ModChains {
+    /// The mods run on pages outside every configured directory.
+    pub default: Vec<String>,
+
+    /// The mods run on pages under a directory, relative to the
+    /// root. The deepest matching directory wins.
+    pub dirs: BTreeMap<PathBuf, Vec<String>>
+}
+
+impl ModChains {
+    /// Get the mods to run on the page at a URI.
+    #[inline]
+    #[must_use]
+    pub fn for_uri(&self, uri: &Uri) -> &[String] {
+        let path = Path::new(uri.path().trim_start_matches('/'));
+
+        self.dirs
+            .iter()
+            .filter(|&(dir, _)| path.starts_with(dir))
+            .max_by_key(|&(dir, _)| dir.components().count())
+            .map_or(&self.default, |(_, mods)| mods)
+    }
+
+    /// Run the same mods on every page.
+    #[inline]
+    #[must_use]
+    pub const fn new(default: Vec<String>) -> Self {
+        Self {
+            default,
+            dirs: BTreeMap::new()
         }
-    };
+    }
+
+    /// Run different mods on pages under a directory.
+    #[inline]
+    #[must_use]
+    pub fn with_dir(mut self, dir: PathBuf, mods: Vec<String>) -> Self {
+        self.dirs.insert(dir, mods);
+        self
+    }
+}
+
+impl Default for ModChains {
+    #[inline]
+    fn default() -> Self {
+        Self::new(DEFAULT_MODS.map(ToOwned::to_owned).to_vec())
+    }
+}
+
+/// HTML after running it through the reqaz-builtin mods.
+#[non_exhaustive]
+#[expect(clippy::module_name_repetitions, reason = "it is what `process_html` returns, so it shares the name")]
+pub struct ProcessedHtml {
+    /// Every file the mods read while processing.
+    pub dependencies: BTreeSet<PathBuf>,
+
+    /// The resulting HTML.
+    pub html: String,
+
+    /// Every remote URL the mods fetched while processing.
+    pub remote: BTreeSet<String>,
+
+    /// Problems the mods ran into that didn't stop processing,
+    /// like fetches that failed.
+    pub warnings: Vec<String>
 }
 
 /// Process HTML using reqaz-builtin mods and kuchikiki
@@ -43,8 +101,30 @@ pub fn process_html(
     resolver: &SourceResolver,
     uri: &Uri,
     html: String,
-) -> Result<String, mods::Error> {
+) -> Result<ProcessedHtml, mods::Error> {
     let dom = kuchikiki::parse_html().one(html);
 
-    apply_internal_mods!(resolver, uri, dom, ["fetch", "css", "component"])
+    let mut mod_manager = HtmlModManager {
+        chain: vec![],
+        dependencies: RefCell::default(),
+        mod_cache: HashMap::default(),
+        page_uri: uri.clone(),
+        remote: RefCell::default(),
+        resolver: resolver.clone(),
+        warnings: RefCell::default()
+    };
+
+    mod_manager.load_mods(resolver.mods.for_uri(uri).iter().map(String::as_str));
+
+    if resolver.live_reload {
+        mod_manager.load_mods(["reload"]);
+    }
+
+    mod_manager.apply_mods(dom)
+        .map(|new_dom| ProcessedHtml {
+            html: new_dom.to_string(),
+            dependencies: mod_manager.dependencies.take(),
+            remote: mod_manager.remote.take(),
+            warnings: mod_manager.warnings.take()
+        })
 }
@@ -16,6 +16,7 @@ pub trait GetAttr {
 }
 
 impl GetAttr for ElementData {
+    #[inline]
     fn get_attr(&self, name: &str) -> Option<String> {
         self.attributes
             .try_borrow()
@@ -28,9 +29,10 @@ impl GetAttr for ElementData {
 
 /// An href URL.
 /// 
-/// This is used internally to tell where a resource
-/// should be fetched from.
+/// This is used to tell where a resource should be
+/// fetched from, or where a link points to.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum Href {
     /// A full URI (https://google.com)
     Uri(Uri),
@@ -44,12 +46,16 @@ pub enum Href {
     /// Any other href entry. This can include:
     /// 
     /// - JavaScript (javascript:*)
+    /// - Other schemes without a host (mailto:*, tel:*, data:*)
+    /// - Fragments and queries on the same page (#top, ?page=2)
+    /// - Protocol-relative URLs (//example.com/logo.png)
     Other(String)
 }
 
 #[allow(clippy::absolute_paths)]
 #[allow(clippy::pattern_type_mismatch)]
 impl Display for Href {
+    #[inline]
     fn fmt(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             Self::Uri(uri) => uri.fmt(formatter),
@@ -61,6 +67,7 @@ impl Display for Href {
 
 impl Href {
     /// Get the file extension for an href url, if applicable.
+    #[inline]
     #[allow(clippy::pattern_type_mismatch)]
     pub fn extension(&self) -> Option<String> {
         match self {
@@ -76,23 +83,35 @@ impl Href {
 
     /// Append the href to the end of a URI, effectively
     /// resolving it relative to a location.
+    ///
+    /// Relative paths resolve against the directory of the
+    /// location, the same way browsers resolve them. The query
+    /// of the location is not carried over.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the resolved path isn't a valid URI.
+    #[inline]
     pub fn append_to_uri(self, uri: &Uri) -> Result<Uri, InvalidUriParts> {
         match self {
             Self::Uri(uri_entry) => Ok(uri_entry),
             Self::Absolute(path) |
             Self::Relative(path) => {
                 let mut parts = uri.clone().into_parts();
-                let uri_path = PathBuf::from(uri.path())
+                let location = PathBuf::from(uri.path());
+
+                let base_dir = if uri.path().ends_with('/') {
+                    location.as_path()
+                } else {
+                    location.parent().unwrap_or(&location)
+                };
+
+                let uri_path = base_dir
                     .join(path)
                     .to_string_lossy()
                     .to_string();
 
-                let uri_query = uri.query();
-                let uri_path_query = uri_query
-                    .map(|query| format!("{uri_path}?{query}"))
-                    .unwrap_or(uri_path);
-
-                parts.path_and_query = PathAndQuery::from_str(&uri_path_query).ok();
+                parts.path_and_query = PathAndQuery::from_str(&uri_path).ok();
 
                 Uri::from_parts(parts)
             },
@@ -104,12 +123,18 @@ impl Href {
 impl TryFrom<&str> for Href {
     type Error = InvalidUri;
 
+    #[inline]
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        // Check for javascript:
-        if value.starts_with("javascript:") {
+        // Check for links within the same page
+        if value.starts_with('#') || value.starts_with('?') {
+            return Ok(Self::Other(value.to_owned()))
+        }
+
+        // Check for protocol-relative URLs, which have no scheme to fetch with
+        if value.starts_with("//") {
             return Ok(Self::Other(value.to_owned()))
         }
-        
+
         // Check for starting slash
         if value.starts_with('/') {
             let path = PathBuf::from(value);
@@ -121,9 +146,24 @@ impl TryFrom<&str> for Href {
             return Uri::from_str(value).map(Self::Uri);
         }
 
+        // Check for other schemes (javascript:, mailto:...)
+        if has_scheme(value) {
+            return Ok(Self::Other(value.to_owned()))
+        }
+
         // Relative path (others not converted valid yet)
         let path = PathBuf::from(value);
 
         Ok(Self::Relative(path))
     }
 }
+
+/// Whether an href starts with a URI scheme, like `mailto:`.
+#[expect(clippy::single_call_fn, reason = "names the scheme check `try_from` makes")]
+fn has_scheme(value: &str) -> bool {
+    value.split_once(':')
+        .is_some_and(|(scheme, _)| {
+            scheme.starts_with(|chr: char| chr.is_ascii_alphabetic())
+                && scheme.chars().all(|chr| chr.is_ascii_alphanumeric() || matches!(chr, '+' | '-' | '.'))
+        })
+}
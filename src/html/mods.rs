@@ -1,7 +1,12 @@
+extern crate alloc;
+
+use alloc::collections::BTreeSet;
+use core::cell::RefCell;
 use crate::source::SourceResolver;
 use eyre::eyre;
 use hyper::Uri;
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 use super::Html;
 
@@ -20,6 +25,14 @@ mod fetch;
 /// version of `fetch`.
 mod component;
 
+/// The query internal mod, which fills in page
+/// params from the page URI query string.
+mod query;
+
+/// The reload internal mod, which injects the
+/// live-reload client script into served pages.
+mod reload;
+
 /// reqaz HTML mod Error type
 pub type Error = eyre::Report;
 
@@ -32,17 +45,44 @@ pub trait HtmlMod {
 /// An HTML mod manager, used to load mods ahead of time without
 /// creating them multiple times per request.
 pub struct HtmlModManager {
-    /// The URI of the currently loading asset
-    pub page_uri: Uri,
+    /// The names of the mods to apply, in order.
+    pub chain: Vec<String>,
+
+    /// Every file read by mods so far.
+    pub dependencies: RefCell<BTreeSet<PathBuf>>,
 
     /// The mod cache
     pub mod_cache: HashMap<String, Box<dyn HtmlMod>>,
 
-    /// A resolver, for the fetch internal mod
+    /// The URI of the currently loading asset
+    pub page_uri: Uri,
+
+    /// Every remote URL fetched by mods so far.
+    pub remote: RefCell<BTreeSet<String>>,
+
+    /// A resolver, for the fetch internal mod.
     pub resolver: SourceResolver,
+
+    /// Problems mods ran into that didn't stop them.
+    pub warnings: RefCell<Vec<String>>
 }
 
 impl HtmlModManager {
+    /// Record files a mod read while modifying.
+    pub fn add_dependencies<I: IntoIterator<Item = PathBuf>>(&self, paths: I) {
+        self.dependencies.borrow_mut().extend(paths);
+    }
+
+    /// Record remote URLs a mod fetched while modifying.
+    pub fn add_remote<I: IntoIterator<Item = String>>(&self, urls: I) {
+        self.remote.borrow_mut().extend(urls);
+    }
+
+    /// Record problems a mod ran into without failing.
+    pub fn add_warnings<I: IntoIterator<Item = String>>(&self, warnings: I) {
+        self.warnings.borrow_mut().extend(warnings);
+    }
+
     /// Load an internal mod
     fn load_mod(&mut self, mod_name: &str) -> Option<Box<dyn HtmlMod>> {
         let mod_box: Box<dyn HtmlMod> = match mod_name {
@@ -55,18 +95,22 @@ impl HtmlModManager {
                 self.page_uri.clone(),
                 self.resolver.clone(),
             )),
+            "query" => Box::new(query::Mod::new(self.page_uri.clone())),
+            "reload" => Box::<reload::Mod>::default(),
             _ => return None,
         };
 
         Some(mod_box)
     }
 
-    /// Load a set of internal mods
-    pub fn load_mods<const N: usize>(&mut self, mods: [&str; N]) {
+    /// Load a set of internal mods, adding them to the end of the chain.
+    pub fn load_mods<'name, I: IntoIterator<Item = &'name str>>(&mut self, mods: I) {
         for mod_name in mods {
             if let Some(mod_) = self.load_mod(mod_name) {
                 self.mod_cache.insert(mod_name.to_owned(), mod_);
             }
+
+            self.chain.push(mod_name.to_owned());
         }
     }
 
@@ -78,16 +122,14 @@ impl HtmlModManager {
     /// Apply a mod to an HTML fragment, returning the result
     pub fn apply_mod(&self, html: Html, mod_name: &str) -> Result<Html, Error> {
         self.get_mod(mod_name)
-            .ok_or(eyre!("Mod does not exist"))
+            .ok_or_else(|| eyre!("Mod `{mod_name}` does not exist"))
             .and_then(|mod_| mod_.modify(html, self))
     }
 
-    /// Apply all cached mods
+    /// Apply every mod in the chain, in order.
     pub fn apply_mods(&self, html: Html) -> Result<Html, Error> {
-        let mods = self.mod_cache.keys();
-
         let mut out = html;
-        for mod_name in mods {
+        for mod_name in &self.chain {
             out = self.apply_mod(out, mod_name)?;
         }
 
@@ -4,9 +4,10 @@ use super::HtmlMod;
 use super::HtmlModManager;
 use crate::html::attr::{GetAttr, Href};
 use crate::mediatype::TEXT_HTML;
-use crate::source::{ResolverError, SourceResolver};
+use crate::source::{fetch_remote, RemoteError, ResolverError, SourceResolver};
 use html5ever::QualName;
 use html5ever::{local_name, namespace_url, ns};
+use eyre::WrapErr as _;
 use html_escape::decode_html_entities;
 use http::uri::InvalidUriParts;
 use http::Uri;
@@ -14,10 +15,8 @@ use kuchikiki::traits::TendrilSink;
 use kuchikiki::ElementData;
 use kuchikiki::NodeData::DocumentFragment;
 use kuchikiki::NodeRef;
-use mediatype::MediaTypeBuf;
 use std::collections::{HashMap, VecDeque};
 use std::fmt::Display;
-use std::io::Error as IoError;
 
 /// The component reqaz HTML mods
 ///
@@ -37,7 +36,7 @@ impl Mod {
     }
 
     /// Fetch an HTML node for an href and return it
-    fn perform_fetch(&self, href: Href) -> Result<Html, ComponentModError> {
+    fn perform_fetch(&self, href: Href, manager: &HtmlModManager) -> Result<Html, ComponentModError> {
         if href.extension() != Some("html".to_string()) {
             return Err(ComponentModError::LinkNotHtml);
         }
@@ -50,26 +49,27 @@ impl Mod {
                     self.resolver
                         .resolve_source(&uri)
                         .map_err(ComponentModError::ResolverError)
-                        .map(|resolved| resolved.body)
-                }),
-            Href::Uri(uri) => ureq::get(&uri.to_string())
-                .call()
-                .map_err(|err| ComponentModError::Network(Box::new(err)))
-                .and_then(|resp| {
-                    let mime = MediaTypeBuf::from_string(resp.content_type().to_owned()).ok();
-
-                    if mime == Some(TEXT_HTML.into()) {
-                        let mut body = vec![];
-
-                        resp.into_reader()
-                            .read_to_end(&mut body)
-                            .map_err(ComponentModError::IoError)?;
-
-                        Ok(body)
-                    } else {
-                        Err(ComponentModError::LinkNotHtml)
-                    }
+                        .map(|resolved| {
+                            manager.add_dependencies(resolved.dependencies);
+                            manager.add_remote(resolved.remote);
+                            manager.add_warnings(resolved.warnings);
+
+                            resolved.body
+                        })
                 }),
+            Href::Uri(uri) => {
+                manager.add_remote([uri.to_string()]);
+
+                fetch_remote(&self.resolver, &uri)
+                    .map_err(ComponentModError::Remote)
+                    .and_then(|resp| {
+                        if resp.mime == TEXT_HTML {
+                            Ok(resp.body)
+                        } else {
+                            Err(ComponentModError::LinkNotHtml)
+                        }
+                    })
+            },
             Href::Other(_) => Err(ComponentModError::InvalidHref(href.clone())),
         }?;
 
@@ -99,7 +99,7 @@ fn html_from_string(s: &str) -> Result<Html, ComponentModError> {
 }
 
 /// Insert possible props into locations for an HTML segment
-fn process_props(html: Html, props: ComponentData) -> Html {
+pub(super) fn process_props(html: Html, props: &ComponentData) -> Html {
     let mut to_check = html.children().into_iter().collect::<VecDeque<_>>();
 
     while let Some(to_check_el) = to_check.pop_front() {
@@ -131,8 +131,7 @@ fn process_props(html: Html, props: ComponentData) -> Html {
                 continue;
             };
 
-            let node = process_props(node, props.clone());
-            let title_text = node.to_string();
+            let title_text = process_props(node, props).to_string();
 
             // Replace the title node
             let new_node =
@@ -145,7 +144,7 @@ fn process_props(html: Html, props: ComponentData) -> Html {
             to_check_el.insert_after(new_node);
             to_check_el.detach();
         } else if &node_el.name.local == "source" {
-            if node_el.get_attr("slot").is_none() {
+            if node_el.get_attr("slot").is_none() || props.partial {
                 continue;
             }
 
@@ -162,10 +161,29 @@ fn process_props(html: Html, props: ComponentData) -> Html {
                 continue;
             };
 
-            let value = props.props.get(&name).cloned().unwrap_or_default();
+            let Some(value) = props.props
+                .get(&name)
+                .cloned()
+                .or_else(|| (!props.partial).then(String::new))
+            else {
+                continue;
+            };
 
-            to_check_el.insert_after(NodeRef::new_text(value));
-            to_check_el.detach();
+            let in_object = to_check_el
+                .parent()
+                .and_then(|parent| {
+                    parent.as_element().map(|parent_el| parent_el.name.local == local_name!("object"))
+                })
+                .unwrap_or(false);
+
+            if in_object && props.partial {
+                // Params of a component on the page get a value to
+                // pass on instead, for the component mod to read
+                node_el.attributes.borrow_mut().insert("value", value);
+            } else {
+                to_check_el.insert_after(NodeRef::new_text(value));
+                to_check_el.detach();
+            }
         } else {
             // Add children
             for child in to_check_el.children() {
@@ -214,7 +232,11 @@ fn get_props_from_object(node: &Html) -> ComponentData {
         slot.append(child)
     }
 
-    ComponentData { props, slot }
+    ComponentData {
+        partial: false,
+        props,
+        slot,
+    }
 }
 
 fn get_props_from_link(node: &ElementData) -> ComponentData {
@@ -239,6 +261,7 @@ fn get_props_from_link(node: &ElementData) -> ComponentData {
         .collect::<HashMap<_, _>>();
 
     ComponentData {
+        partial: false,
         props,
         slot: NodeRef::new(DocumentFragment),
     }
@@ -286,15 +309,22 @@ impl HtmlMod for Mod {
                 _ => unreachable!(),
             };
 
-            let new_el = href.map(|url| self.perform_fetch(url)).transpose()?;
+            let new_el = href
+                .map(|url| {
+                    let url_str = url.to_string();
+
+                    self.perform_fetch(url, manager)
+                        .wrap_err_with(|| format!("Failed to load component `{url_str}`"))
+                })
+                .transpose()?;
 
             if let Some(new_el) = new_el {
-                let new_el = process_props(new_el, props);
+                let with_props = process_props(new_el, &props);
 
                 // Apply mods to element
-                let new_el = manager.apply_mods(new_el)?;
+                let modded = manager.apply_mods(with_props)?;
 
-                node.insert_after(new_el);
+                node.insert_after(modded);
                 node.detach();
             }
         }
@@ -316,17 +346,12 @@ pub enum ComponentModError {
     /// There was a problem creating a URI at some point
     InvalidUriParts(InvalidUriParts),
 
-    /// There was a networking problem
-    ///
-    /// Wrapped in a box for size concerns
-    Network(Box<ureq::Error>),
+    /// A remote component couldn't be fetched.
+    Remote(RemoteError),
 
     /// There was a resolver error
     ResolverError(ResolverError),
 
-    /// There was an IO problem
-    IoError(IoError),
-
     /// The component was not HTML
     LinkNotHtml,
 }
@@ -339,7 +364,18 @@ impl From<InsertResponseError> for ComponentModError {
 
 #[allow(clippy::missing_trait_methods)]
 #[allow(clippy::absolute_paths)]
-impl std::error::Error for ComponentModError {}
+impl core::error::Error for ComponentModError {
+    #[expect(clippy::pattern_type_mismatch, reason = "each cause is borrowed from the error")]
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Insertion(ire) => ire.source(),
+            Self::InvalidUriParts(iup) => iup.source(),
+            Self::Remote(remote_error) => remote_error.source(),
+            Self::ResolverError(resolver_error) => resolver_error.source(),
+            Self::InvalidHref(_) | Self::LinkNotHtml => None,
+        }
+    }
+}
 
 #[allow(clippy::absolute_paths)]
 #[allow(clippy::pattern_type_mismatch)]
@@ -349,9 +385,8 @@ impl Display for ComponentModError {
             Self::InvalidHref(href) => formatter.write_fmt(format_args!("Invalid href: {href}")),
             Self::Insertion(ire) => ire.fmt(formatter),
             Self::InvalidUriParts(iup) => iup.fmt(formatter),
-            Self::Network(neterr) => neterr.fmt(formatter),
+            Self::Remote(remote_error) => remote_error.fmt(formatter),
             Self::ResolverError(resolver_error) => resolver_error.fmt(formatter),
-            Self::IoError(ioerr) => ioerr.fmt(formatter),
             Self::LinkNotHtml => {
                 formatter.write_str("The component at the specific link is not valid HTML")
             }
@@ -359,8 +394,13 @@ impl Display for ComponentModError {
     }
 }
 
+/// Props and slot contents to fill into an HTML segment.
 #[derive(Clone)]
-struct ComponentData {
+pub(super) struct ComponentData {
+    /// Leave params without a matching prop and slots as they are,
+    /// so they can be filled in later.
+    pub partial: bool,
+
     pub props: HashMap<String, String>,
     pub slot: Html,
 }
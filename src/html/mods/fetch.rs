@@ -2,18 +2,17 @@ use super::Html;
 use super::HtmlMod;
 use super::HtmlModManager;
 use crate::html::attr::{GetAttr, Href};
-use crate::mediatype::{APPLICATION_OCTET_STREAM, IMG_SVG_XML, TEXT_CSS, TEXT_HTML};
-use crate::source::{ResolverError, SourceResolver};
+use crate::mediatype::{IMG_SVG_XML, TEXT_CSS, TEXT_HTML};
+use crate::source::{fetch_remote, RemoteError, ResolverError, SourceResolver};
 use core::fmt::Display;
 use html5ever::QualName;
 use html5ever::{local_name, namespace_url, ns};
-use http::uri::InvalidUriParts;
+use http::uri::{InvalidUri, InvalidUriParts};
 use hyper::Uri;
 use kuchikiki::traits::TendrilSink;
 use kuchikiki::NodeData::DocumentFragment;
 use kuchikiki::NodeRef;
 use mediatype::MediaTypeBuf;
-use std::io::Error as IoError;
 
 /// The Fetch reqaz HTML mod
 pub struct Mod {
@@ -32,7 +31,7 @@ impl Mod {
     }
 
     /// Fetch an HTML node for an href and return it
-    fn perform_fetch(&self, href: Href) -> Result<Html, FetchError> {
+    fn perform_fetch(&self, href: Href, manager: &HtmlModManager) -> Result<Html, FetchError> {
         href.extension()
             .and_then(|ext| get_element_from_extension(&ext))
             .ok_or_else(|| FetchError::InvalidHref(href.clone()))
@@ -44,26 +43,33 @@ impl Mod {
                         .and_then(|uri| {
                             self.resolver
                                 .resolve_source(&uri)
-                                .map_err(FetchError::ResolverError)
-                                .map(|resolved| FetchResponse {
-                                    body: resolved.body,
-                                    mime: resolved.mime.into(),
+                                .map_err(|err| {
+                                    // The page still depends on a file it failed
+                                    // to fetch, so fixing that file is a change
+                                    if let Ok(path) = self.resolver.get_path_from_uri(&uri) {
+                                        manager.add_dependencies([path]);
+                                    }
+
+                                    FetchError::ResolverError(err)
+                                })
+                                .map(|resolved| {
+                                    manager.add_dependencies(resolved.dependencies);
+                                    manager.add_remote(resolved.remote);
+                                    manager.add_warnings(resolved.warnings);
+
+                                    FetchResponse {
+                                        body: resolved.body,
+                                        mime: resolved.mime.into(),
+                                    }
                                 })
                         }),
-                    Href::Uri(uri) => ureq::get(&uri.to_string())
-                        .call()
-                        .map_err(|err| FetchError::Network(Box::new(err)))
-                        .and_then(|resp| {
-                            let mime = MediaTypeBuf::from_string(resp.content_type().to_owned())
-                                .unwrap_or_else(|_| APPLICATION_OCTET_STREAM.into());
-
-                            let mut body = vec![];
-
-                            resp.into_reader()
-                                .read_to_end(&mut body)
-                                .map_err(FetchError::IoError)
-                                .map(|_| FetchResponse { body, mime })
-                        }),
+                    Href::Uri(uri) => {
+                        manager.add_remote([uri.to_string()]);
+
+                        fetch_remote(&self.resolver, &uri)
+                            .map_err(FetchError::Remote)
+                            .map(|resp| FetchResponse { body: resp.body, mime: resp.mime })
+                    },
                     Href::Other(_) => Err(FetchError::InvalidHref(href.clone())),
                 }
                 .map(|resp| (element, resp))
@@ -115,7 +121,7 @@ fn insert_response(el: Html, resp: FetchResponse) -> Result<Html, InsertResponse
 }
 
 impl HtmlMod for Mod {
-    fn modify(&self, html: Html, _manager: &HtmlModManager) -> Result<Html, eyre::Error> {
+    fn modify(&self, html: Html, mod_manager: &HtmlModManager) -> Result<Html, eyre::Error> {
         let nib_imports: Vec<_> = html
             .select(r#"[nib-mod~="fetch"]"#)
             .map(|sels| sels.into_iter().collect())
@@ -123,16 +129,28 @@ impl HtmlMod for Mod {
 
         for css_match in nib_imports {
             let nib_item = css_match.as_node();
-            let res = nib_item
+            let Some(href_str) = nib_item
                 .as_element()
                 .and_then(|nib_el| nib_el.get_attr("href"))
-                .and_then(|href| Href::try_from(href.as_str()).ok())
-                .map(|href| self.perform_fetch(href))
-                .transpose();
-
-            if let Ok(Some(new_el)) = res {
-                nib_item.insert_after(new_el);
-                nib_item.detach();
+            else {
+                mod_manager.add_warnings(["Fetch element has no `href`".to_owned()]);
+                continue;
+            };
+
+            // Failed fetches leave the element alone, so one missing
+            // resource doesn't take the whole page down with it
+            let res = Href::try_from(href_str.as_str())
+                .map_err(FetchError::InvalidUri)
+                .and_then(|href| self.perform_fetch(href, mod_manager));
+
+            match res {
+                Ok(new_el) => {
+                    nib_item.insert_after(new_el);
+                    nib_item.detach();
+                },
+                Err(err) => mod_manager.add_warnings([
+                    format!("Failed to fetch `{href_str}`: {:#}", eyre::Report::new(err))
+                ])
             }
         }
 
@@ -165,24 +183,34 @@ pub enum FetchError {
     /// There was a problem inserting an element
     Insertion(InsertResponseError),
 
+    /// The href couldn't be parsed as a URI.
+    InvalidUri(InvalidUri),
+
     /// There was a problem creating a URI at some point
     InvalidUriParts(InvalidUriParts),
 
-    /// There was a networking problem
-    ///
-    /// Wrapped in a box for size concerns
-    Network(Box<ureq::Error>),
+    /// A remote resource couldn't be fetched.
+    Remote(RemoteError),
 
     /// There was a resolver error
     ResolverError(ResolverError),
-
-    /// There was an IO problem
-    IoError(IoError),
 }
 
 #[allow(clippy::missing_trait_methods)]
 #[allow(clippy::absolute_paths)]
-impl std::error::Error for FetchError {}
+impl core::error::Error for FetchError {
+    #[expect(clippy::pattern_type_mismatch, reason = "the source is a borrow of the wrapped error")]
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            Self::Insertion(ire) => ire.source(),
+            Self::InvalidUri(iu) => iu.source(),
+            Self::InvalidUriParts(iup) => iup.source(),
+            Self::Remote(remote_error) => remote_error.source(),
+            Self::ResolverError(resolver_error) => resolver_error.source(),
+            Self::InvalidHref(_) => None,
+        }
+    }
+}
 
 #[allow(clippy::absolute_paths)]
 #[allow(clippy::pattern_type_mismatch)]
@@ -191,10 +219,10 @@ impl Display for FetchError {
         match self {
             Self::InvalidHref(href) => formatter.write_fmt(format_args!("Invalid href: {href}")),
             Self::Insertion(ire) => ire.fmt(formatter),
+            Self::InvalidUri(iu) => iu.fmt(formatter),
             Self::InvalidUriParts(iup) => iup.fmt(formatter),
-            Self::Network(neterr) => neterr.fmt(formatter),
+            Self::Remote(remote_error) => remote_error.fmt(formatter),
             Self::ResolverError(resolver_error) => resolver_error.fmt(formatter),
-            Self::IoError(ioerr) => ioerr.fmt(formatter),
         }
     }
 }
@@ -0,0 +1,66 @@
+use super::component::{process_props, ComponentData};
+use super::Html;
+use super::HtmlMod;
+use super::HtmlModManager;
+use eyre::Result;
+use http::Uri;
+use kuchikiki::NodeData::DocumentFragment;
+use kuchikiki::NodeRef;
+use percent_encoding::percent_decode_str;
+use std::collections::HashMap;
+
+/// The query reqaz HTML mod.
+///
+/// This mod fills in `<param name>` elements of a page with the
+/// query parameters of its URI, the same way the component mod
+/// fills in props. Params not in the query are left alone, so
+/// components can still fill them in.
+pub struct Mod {
+    /// The URI of the currently loading asset.
+    page_uri: Uri,
+}
+
+impl Mod {
+    /// Create a new Query mod instance.
+    #[expect(clippy::single_call_fn, reason = "mods are only created when the manager loads them")]
+    pub const fn new(page_uri: Uri) -> Self {
+        Self { page_uri }
+    }
+}
+
+impl HtmlMod for Mod {
+    fn modify(&self, html: Html, _manager: &HtmlModManager) -> Result<Html, eyre::Error> {
+        let Some(query) = self.page_uri.query() else {
+            return Ok(html);
+        };
+
+        let props = ComponentData {
+            partial: true,
+            props: query_props(query),
+            slot: NodeRef::new(DocumentFragment),
+        };
+
+        Ok(process_props(html, &props))
+    }
+}
+
+/// Parse a query string into props. Later values win.
+#[expect(clippy::single_call_fn, reason = "keeps `modify` about filling in the page")]
+fn query_props(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+
+            (decode_query_part(name), decode_query_part(value))
+        })
+        .collect()
+}
+
+/// Decode a percent-encoded query name or value, where `+` is a space.
+fn decode_query_part(part: &str) -> String {
+    percent_decode_str(&part.replace('+', " "))
+        .decode_utf8_lossy()
+        .into_owned()
+}
@@ -0,0 +1,58 @@
+use super::Html;
+use super::HtmlMod;
+use super::HtmlModManager;
+use crate::reload::RELOAD_SCRIPT;
+use eyre::Result;
+use html5ever::{local_name, namespace_url, ns, LocalName, QualName};
+use kuchikiki::{Attribute, ExpandedName};
+
+/// The attribute marking an injected reload script.
+const RELOAD_ATTR: &str = "data-reqaz-reload";
+
+/// The reload reqaz HTML mod.
+///
+/// This mod injects the live-reload client script, and is
+/// only loaded when the resolver has live reloading enabled.
+#[derive(Default)]
+pub struct Mod;
+
+impl HtmlMod for Mod {
+    fn modify(&self, html: Html, _manager: &HtmlModManager) -> Result<Html, eyre::Error> {
+        // Components are resolved as pages too, so they carry
+        // their own scripts. Only one should be left over.
+        let injected: Vec<_> = html
+            .select(&format!("script[{RELOAD_ATTR}]"))
+            .map(|sels| sels.into_iter().collect())
+            .unwrap_or_default();
+
+        for script in injected {
+            script.as_node().detach();
+        }
+
+        let script_node = Html::new_element(
+            QualName::new(None, ns!(html), local_name!("script")),
+            vec![(
+                ExpandedName::new(ns!(), LocalName::from(RELOAD_ATTR)),
+                Attribute {
+                    prefix: None,
+                    value: String::new(),
+                },
+            )],
+        );
+
+        script_node.append(Html::new_text(RELOAD_SCRIPT));
+
+        let body = html
+            .select_first("body")
+            .ok()
+            .map(|node_data| node_data.as_node().to_owned());
+
+        if let Some(body_ref) = body {
+            body_ref.append(script_node);
+        } else {
+            html.append(script_node);
+        }
+
+        Ok(html)
+    }
+}
@@ -19,7 +19,14 @@
 #![allow(clippy::self_named_module_files)]
 #![allow(clippy::unseparated_literal_suffix)]
 #![allow(clippy::pub_with_shorthand)]
+#![expect(clippy::question_mark_used, reason = "errors are passed up with `?` throughout")]
 
+pub mod caching;
+pub mod compress;
 pub mod html;
 pub mod mediatype;
+pub mod proxy;
+pub mod range;
+pub mod reload;
 pub mod source;
+pub mod watch;
@@ -19,25 +19,26 @@
 #![allow(clippy::blanket_clippy_restriction_lints)]
 #![allow(clippy::implicit_return)]
 #![allow(clippy::unseparated_literal_suffix)]
+#![expect(clippy::self_named_module_files, reason = "`cli.rs` sits next to `cli/` like the library's modules")]
+#![expect(clippy::question_mark_used, reason = "errors are passed up with `?` throughout")]
+
+/// CLI-only parts of reqaz.
+mod cli;
 
 use clap::{Parser, Subcommand};
+use cli::check::CheckFormat;
+use cli::config::{CliConfig, Protocol};
 use color_eyre::Result;
-use core::net::SocketAddr;
-use core::str::FromStr;
+use core::net::IpAddr;
 use eyre::eyre;
-use http::uri::{Uri, Authority};
-use hyper::server::conn::http1;
-use hyper_util::rt::TokioIo;
-use reqaz::source::{SourceResolver, SourceService};
-use serde::{Serialize, Deserialize};
+use reqaz::source::SourceResolver;
 use std::env::current_dir;
 use std::path::PathBuf;
-use tokio::net::TcpListener;
-use tokio::task::spawn as tokio_spawn;
-
+use std::process::ExitCode;
 
 /// Requests from A to Z
 #[derive(Parser)]
+#[expect(clippy::arbitrary_source_item_ordering, reason = "clap lists the options in the order of the fields")]
 struct Cli {
     /// The path to serve from
     #[arg(
@@ -45,6 +46,12 @@ struct Cli {
     )]
     path: Option<PathBuf>,
 
+    /// The address to serve on.
+    #[arg(
+        long = "host"
+    )]
+    host: Option<IpAddr>,
+
     /// The port to serve from
     #[arg(
         short = 'p',
@@ -52,12 +59,40 @@ struct Cli {
     )]
     port: Option<u16>,
 
+    /// The HTTP version to serve.
+    #[arg(
+        long = "protocol",
+        value_enum
+    )]
+    protocol: Option<Protocol>,
+
     /// Whether to print logs on request status
     #[arg(
         long = "log"
     )]
     log: Option<bool>,
 
+    /// Whether to reload open pages when files change (serve only).
+    #[arg(
+        long = "live-reload"
+    )]
+    live_reload: Option<bool>,
+
+    /// Keep building other pipelines after one fails.
+    #[arg(
+        long = "keep-going",
+        global = true
+    )]
+    keep_going: bool,
+
+    /// How many outputs to build at once.
+    #[arg(
+        short = 'j',
+        long = "jobs",
+        global = true
+    )]
+    jobs: Option<usize>,
+
     /// Subcommand to run
     #[clap(subcommand)]
     subcommand: Option<SubCli>
@@ -65,15 +100,57 @@ struct Cli {
 
 /// Subcommand to run
 #[derive(Subcommand)]
+#[expect(clippy::arbitrary_source_item_ordering, reason = "clap lists the subcommands in the order of the variants")]
 enum SubCli {
     /// Serve files in root, do not build pipelines
-    Serve
+    Serve,
+
+    /// Check every pipeline for problems, without writing anything.
+    Check {
+        /// How to print the report.
+        #[arg(
+            long = "format",
+            value_enum,
+            default_value = "human"
+        )]
+        format: CheckFormat
+    },
+
+    /// Build every pipeline into the output directory.
+    Build {
+        /// Keep running, and rebuild whenever a file under root changes.
+        #[arg(
+            long = "watch"
+        )]
+        watch: bool
+    },
+
+    /// Show what a build would change in the output directory, without writing anything.
+    Diff,
+
+    /// Serve the output directory as plain static files, without building.
+    Preview,
+
+    /// Remove outputs of previous builds that no pipeline produces anymore.
+    Clean {
+        /// List what would be removed, without removing anything.
+        #[arg(
+            long = "dry-run"
+        )]
+        dry_run: bool,
+
+        /// Remove every output, the manifest and the build cache.
+        #[arg(
+            long = "all"
+        )]
+        all: bool
+    }
 }
 
 #[tokio::main]
 #[allow(clippy::question_mark_used)]
 #[allow(clippy::absolute_paths)]
-async fn main() -> Result<()> {
+async fn main() -> Result<ExitCode> {
     color_eyre::install()?;
 
     let args = Cli::parse();
@@ -90,136 +167,64 @@ async fn main() -> Result<()> {
         }
     }?;
 
-    let authority = Authority::from_str(
-        &format!("localhost:{}", config.port)
-    )?;
-
-    let addr = SocketAddr::from(([127, 0, 0, 1], config.port));
-    let listener = TcpListener::bind(addr).await?;
+    let authority = config.authority()?;
 
     let root = config.clone().root.or_else(|| {
         current_dir().ok()
     }).ok_or(eyre!("No root path provided"))?;
 
-    let generate_config = config.generate;
-    let resolver = SourceResolver::new(root, authority);
-
-    let generate_optional = {
-        if matches!(args.subcommand, Some(SubCli::Serve)) {
-            None
-        } else {
-            generate_config
-        }
-    };
+    let generate_config = config.generate.clone();
+    if matches!(args.subcommand, Some(SubCli::Preview)) {
+        let generate = generate_config.ok_or_else(|| eyre!("No `generate` config to preview"))?;
 
-    #[allow(clippy::print_stdout)]
-    if let Some(generate) = generate_optional {
-        for pipeline in &generate.pipelines {
-            let out_path = generate.output_dir.clone().join(pipeline.output.clone());
+        cli::preview::run(&generate.output_dir, authority, &config).await?;
 
-            if let Ok(resolved) = resolver.resolve_source(&pipeline.input) {
-                tokio::fs::create_dir_all(out_path.parent().unwrap_or(&generate.output_dir)).await?;
-                tokio::fs::write(out_path, resolved.body).await?;
-            }
-        }
-
-        println!("Generated {} pipelines.", generate.pipelines.len());
-
-        Ok(())
-    } else {
-        let service = SourceService::new(
-            resolver,
-            config.log
-        );
-
-        // gee thanks clippy, that's the whole point
-        #[allow(clippy::infinite_loop)]
-        loop {
-            let accepted = listener.accept().await;
-
-            if let Ok((stream, _)) = accepted {
-                let io = TokioIo::new(stream);
-
-                let service_clone = service.clone();
-
-                #[allow(clippy::print_stderr)]
-                tokio_spawn(async move {
-                    if let Err(err) = http1::Builder::new()
-                        .serve_connection(io, &service_clone)
-                        .await
-                    {
-                        eprintln!("Error serving request: {err}");
-                    }
-                });
-            }
-        }
+        return Ok(ExitCode::SUCCESS);
     }
-}
-
-/// Base CLI configuration (reqaz.json)
-#[derive(Serialize, Deserialize, Clone)]
-#[serde(default)]
-struct CliConfig {
-    /// The root folder to serve from
-    pub root: Option<PathBuf>,
 
-    /// The port to serve from
-    pub port: u16,
+    let resolver = SourceResolver::new(root, authority)
+        .with_mods(config.mod_chains())
+        .with_follow_symlinks(config.follow_symlinks);
 
-    /// Enable logging
-    pub log: bool,
+    if let Some(SubCli::Check { format }) = args.subcommand {
+        return cli::check::run(&resolver, generate_config.as_ref(), format).await;
+    }
 
-    /// Generate options
-    pub generate: Option<GenerateConfig>
-}
+    if let Some(SubCli::Build { watch }) = args.subcommand {
+        let generate = generate_config.ok_or_else(|| eyre!("No `generate` config to build"))?;
 
-impl CliConfig {
-    /// Override config with CLI options manually
-    pub fn override_with_cli(mut self, cli: &Cli) -> Self {
-        if let Some(root) = cli.path.clone() {
-            self.root = Some(root);
+        if watch {
+            return cli::generate::watch(&resolver, &generate, args.keep_going).await;
         }
 
-        if let Some(port) = cli.port {
-            self.port = port;
-        }
+        return cli::generate::run(&resolver, &generate, args.keep_going).await;
+    }
 
-        if let Some(log) = cli.log {
-            self.log = log;
-        }
+    if matches!(args.subcommand, Some(SubCli::Diff)) {
+        let generate = generate_config.ok_or_else(|| eyre!("No `generate` config to diff"))?;
 
-        self
+        return cli::diff::run(&resolver, &generate).await;
     }
-}
 
-impl Default for CliConfig {
-    fn default() -> Self {
-        Self {
-            root: None,
-            port: 5000,
-            log: false,
-            generate: None
-        }
-    }
-}
+    if let Some(SubCli::Clean { dry_run, all }) = args.subcommand {
+        let generate = generate_config.ok_or_else(|| eyre!("No `generate` config to clean"))?;
 
-/// Generation configuration
-#[derive(Serialize, Deserialize, Clone)]
-struct GenerateConfig {
-    /// The final output directory of files
-    pub output_dir: PathBuf,
+        return cli::clean::run(&resolver, &generate, dry_run, all);
+    }
 
-    /// List of pipelines to run
-    pub pipelines: Vec<PipelineConfig>
-}
+    let generate_optional = {
+        if matches!(args.subcommand, Some(SubCli::Serve)) {
+            None
+        } else {
+            generate_config
+        }
+    };
 
-/// Pipeline configuration
-#[derive(Serialize, Deserialize, Clone)]
-struct PipelineConfig {
-    /// The input URI
-    #[serde(with = "http_serde::uri")]
-    pub input: Uri,
+    if let Some(generate) = generate_optional {
+        cli::generate::run(&resolver, &generate, args.keep_going).await
+    } else {
+        cli::serve::run(resolver, &config).await?;
 
-    /// The output path, relative to the output dir
-    pub output: PathBuf
+        Ok(ExitCode::SUCCESS)
+    }
 }
@@ -9,6 +9,22 @@ pub const IMG_JPEG: MediaType<'_> = media_type!(IMAGE/JPEG);
 pub const IMG_PNG: MediaType<'_> = media_type!(IMAGE/PNG);
 pub const IMG_WEBP: MediaType<'_> = media_type!(IMAGE/WEBP);
 pub const IMG_GIF: MediaType<'_> = media_type!(IMAGE/GIF);
+pub const IMG_AVIF: MediaType<'_> = media_type!(IMAGE/AVIF);
+pub const IMG_ICON: MediaType<'_> = media_type!(IMAGE/vnd::MICROSOFT_ICON);
+pub const TEXT_JAVASCRIPT: MediaType<'_> = media_type!(TEXT/JAVASCRIPT);
+pub const TEXT_PLAIN: MediaType<'_> = media_type!(TEXT/PLAIN);
+pub const APPLICATION_JSON: MediaType<'_> = media_type!(APPLICATION/JSON);
+pub const APPLICATION_MANIFEST_JSON: MediaType<'_> = media_type!(APPLICATION/MANIFEST+JSON);
+pub const APPLICATION_XML: MediaType<'_> = media_type!(APPLICATION/XML);
+pub const APPLICATION_PDF: MediaType<'_> = media_type!(APPLICATION/PDF);
+pub const APPLICATION_WASM: MediaType<'_> = media_type!(APPLICATION/WASM);
+pub const FONT_WOFF: MediaType<'_> = media_type!(FONT/WOFF);
+pub const FONT_WOFF2: MediaType<'_> = media_type!(FONT/WOFF2);
+pub const VIDEO_MP4: MediaType<'_> = media_type!(VIDEO/MP4);
+pub const VIDEO_WEBM: MediaType<'_> = media_type!(VIDEO/WEBM);
+pub const AUDIO_MPEG: MediaType<'_> = media_type!(AUDIO/MPEG);
+pub const AUDIO_OGG: MediaType<'_> = media_type!(AUDIO/OGG);
+pub const AUDIO_WAV: MediaType<'_> = media_type!(AUDIO/WAV);
 pub const APPLICATION_OCTET_STREAM: MediaType<'_> = media_type!(APPLICATION/OCTET_STREAM);
 
 
@@ -26,10 +42,26 @@ impl GetMediaType for Path {
                     "html" => TEXT_HTML,
                     "css" | "scss" => TEXT_CSS,
                     "svg" => IMG_SVG_XML,
-                    "jpeg" => IMG_JPEG,
+                    "jpeg" | "jpg" => IMG_JPEG,
                     "png" => IMG_PNG,
                     "webp" => IMG_WEBP,
                     "gif" => IMG_GIF,
+                    "avif" => IMG_AVIF,
+                    "ico" => IMG_ICON,
+                    "js" | "mjs" => TEXT_JAVASCRIPT,
+                    "txt" => TEXT_PLAIN,
+                    "json" | "map" => APPLICATION_JSON,
+                    "webmanifest" => APPLICATION_MANIFEST_JSON,
+                    "xml" => APPLICATION_XML,
+                    "pdf" => APPLICATION_PDF,
+                    "wasm" => APPLICATION_WASM,
+                    "woff" => FONT_WOFF,
+                    "woff2" => FONT_WOFF2,
+                    "mp4" => VIDEO_MP4,
+                    "webm" => VIDEO_WEBM,
+                    "mp3" => AUDIO_MPEG,
+                    "ogg" => AUDIO_OGG,
+                    "wav" => AUDIO_WAV,
                     _ => APPLICATION_OCTET_STREAM
                 }
             })
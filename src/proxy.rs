@@ -0,0 +1,161 @@
+use crate::source::ServiceBody;
+use core::cmp::Reverse;
+use core::fmt::Display;
+use http::header::{HeaderMap, HeaderName, HOST};
+use http::uri::{InvalidUriParts, PathAndQuery};
+use http_body_util::{BodyExt as _, Full};
+use hyper::body::{Bytes, Incoming as IncomingBody};
+use hyper::{Request, Response, StatusCode, Uri, Version};
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+
+
+/// Headers that only apply to a single connection, so they're
+/// never forwarded.
+const HOP_BY_HOP_HEADERS: [&str; 8] = [
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade"
+];
+
+/// A rule forwarding requests under a path prefix to another server.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+#[expect(clippy::module_name_repetitions, reason = "pairs with `Proxy`, which `rule_for` hands it out from")]
+pub struct ProxyRule {
+    /// The path prefix requests must start with, like `/api/`.
+    pub prefix: String,
+
+    /// The server to forward them to, like `http://localhost:8080`.
+    pub target: Uri
+}
+
+/// Forwards requests matching proxy rules to other servers.
+#[derive(Clone)]
+pub struct Proxy {
+    /// The client requests are forwarded with.
+    client: Client<HttpConnector, IncomingBody>,
+
+    /// The rules, longest prefix first.
+    rules: Vec<ProxyRule>
+}
+
+impl Proxy {
+    /// Forward a request to the target of a rule over HTTP/1.1,
+    /// keeping its method, headers, path and body, and stream the
+    /// response back. If the forwarded URI can't be built, or the
+    /// target can't be reached, a `502 Bad Gateway` is returned.
+    #[inline]
+    pub async fn forward(&self, rule: &ProxyRule, req: Request<IncomingBody>) -> Response<ServiceBody> {
+        let (mut parts, body) = req.into_parts();
+
+        parts.uri = match target_uri(&rule.target, &parts.uri) {
+            Ok(uri) => uri,
+            Err(err) => return bad_gateway(&err)
+        };
+        parts.version = Version::HTTP_11;
+        remove_hop_by_hop(&mut parts.headers);
+
+        if let Some(authority) = rule.target.authority() {
+            if let Ok(host) = authority.as_str().parse() {
+                parts.headers.insert(HOST, host);
+            }
+        }
+
+        match self.client.request(Request::from_parts(parts, body)).await {
+            Ok(response) => {
+                let (mut response_parts, response_body) = response.into_parts();
+
+                remove_hop_by_hop(&mut response_parts.headers);
+
+                // The connection back to the browser has its own version
+                response_parts.version = Version::default();
+
+                Response::from_parts(
+                    response_parts,
+                    response_body.map_err(Into::into).boxed_unsync()
+                )
+            },
+            Err(err) => bad_gateway(&err)
+        }
+    }
+
+    /// Create a proxy from prefix and target pairs. When several
+    /// prefixes match, the longest wins.
+    #[inline]
+    pub fn new<I: IntoIterator<Item = (String, Uri)>>(rules: I) -> Self {
+        let mut sorted: Vec<ProxyRule> = rules.into_iter()
+            .map(|(prefix, target)| ProxyRule { prefix, target })
+            .collect();
+
+        sorted.sort_by_key(|rule| Reverse(rule.prefix.len()));
+
+        Self {
+            client: Client::builder(TokioExecutor::new()).build_http(),
+            rules: sorted
+        }
+    }
+
+    /// The rule a request URI falls under, if any.
+    #[inline]
+    #[must_use]
+    pub fn rule_for(&self, uri: &Uri) -> Option<&ProxyRule> {
+        self.rules.iter().find(|rule| uri.path().starts_with(&rule.prefix))
+    }
+}
+
+/// A `502 Bad Gateway` response describing why forwarding failed.
+fn bad_gateway(err: &dyn Display) -> Response<ServiceBody> {
+    let mut response = Response::new(
+        Full::new(Bytes::from(format!("Proxy error: {err}")))
+            .map_err(Into::into)
+            .boxed_unsync()
+    );
+
+    *response.status_mut() = StatusCode::BAD_GATEWAY;
+
+    response
+}
+
+/// The URI a request is forwarded to: the target's scheme and
+/// authority, with the target's path in front of the request's.
+///
+/// # Errors
+///
+/// Errors if the joined URI isn't valid.
+#[inline]
+pub fn target_uri(target: &Uri, uri: &Uri) -> Result<Uri, InvalidUriParts> {
+    let mut parts = target.clone().into_parts();
+
+    let base = target.path().trim_end_matches('/');
+    let request_path = uri.path_and_query().map_or("/", PathAndQuery::as_str);
+
+    parts.path_and_query = format!("{base}{request_path}").parse().ok();
+
+    Uri::from_parts(parts)
+}
+
+/// Remove every hop-by-hop header, including any listed in `Connection`.
+#[inline]
+pub fn remove_hop_by_hop(headers: &mut HeaderMap) {
+    let listed: Vec<HeaderName> = headers.get_all("connection")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|name| name.trim().parse().ok())
+        .collect();
+
+    for name in listed {
+        headers.remove(name);
+    }
+
+    for name in HOP_BY_HOP_HEADERS {
+        headers.remove(name);
+    }
+}
@@ -0,0 +1,103 @@
+#![expect(clippy::module_name_repetitions, reason = "bare `Byte` and `Request` would say little about what they hold")]
+
+/// An inclusive range of bytes in a body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ByteRange {
+    /// The last byte in the range.
+    pub end: u64,
+
+    /// The first byte in the range.
+    pub start: u64
+}
+
+impl ByteRange {
+    /// The `Content-Range` header value for the range, in a body
+    /// of `total` bytes.
+    #[inline]
+    #[must_use]
+    pub fn content_range(self, total: u64) -> String {
+        format!("bytes {}-{}/{total}", self.start, self.end)
+    }
+
+    /// Whether the range covers no bytes. Ranges always hold at
+    /// least their first byte, so this is only for completeness.
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(self) -> bool {
+        self.end < self.start
+    }
+
+    /// How many bytes the range covers.
+    #[inline]
+    #[must_use]
+    pub const fn len(self) -> u64 {
+        self.end.saturating_sub(self.start).saturating_add(1)
+    }
+}
+
+/// What part of a body a `Range` header asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RangeRequest {
+    /// The whole body, including for headers that can't be honored.
+    Full,
+
+    /// A single range of bytes.
+    Partial(ByteRange),
+
+    /// A range past the end of the body.
+    Unsatisfiable
+}
+
+impl RangeRequest {
+    /// Parse a `Range` header for a body of `len` bytes. Only a
+    /// single byte range is honored. Several ranges, or a header
+    /// that can't be parsed, get the whole body.
+    #[inline]
+    #[must_use]
+    pub fn parse(header: &str, len: u64) -> Self {
+        let Some(spec) = header.trim().strip_prefix("bytes=") else {
+            return Self::Full;
+        };
+
+        let Some((start, end)) = spec.split_once('-').filter(|_| !spec.contains(',')) else {
+            return Self::Full;
+        };
+
+        let last = len.saturating_sub(1);
+
+        // `bytes=-500` is the last 500 bytes
+        if start.trim().is_empty() {
+            return match end.trim().parse::<u64>() {
+                Ok(0) => Self::Unsatisfiable,
+                Ok(_) if len == 0 => Self::Unsatisfiable,
+                Ok(suffix) => Self::Partial(ByteRange {
+                    end: last,
+                    start: len.saturating_sub(suffix)
+                }),
+                Err(_) => Self::Full
+            };
+        }
+
+        let Ok(first) = start.trim().parse::<u64>() else {
+            return Self::Full;
+        };
+
+        if first >= len {
+            return Self::Unsatisfiable;
+        }
+
+        if end.trim().is_empty() {
+            return Self::Partial(ByteRange { end: last, start: first });
+        }
+
+        match end.trim().parse::<u64>() {
+            Ok(until) if until >= first => Self::Partial(ByteRange {
+                end: until.min(last),
+                start: first
+            }),
+            Ok(_) | Err(_) => Self::Full
+        }
+    }
+}
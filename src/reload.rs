@@ -0,0 +1,267 @@
+extern crate alloc;
+
+use alloc::collections::BTreeSet;
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicU64, Ordering};
+use crate::source::{BodyError, ServiceBody, SourceResolver};
+use crate::watch::FileWatcher;
+use http_body_util::{BodyExt as _, StreamBody};
+use hyper::Uri;
+use hyper::body::{Bytes, Frame};
+use kuchikiki::traits::TendrilSink as _;
+use notify_debouncer_mini::notify::Error as NotifyError;
+use percent_encoding::percent_decode_str;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+use tokio_stream::StreamExt as _;
+use tokio_stream::wrappers::BroadcastStream;
+
+
+/// The path the live-reload event stream is served from.
+pub const RELOAD_PATH: &str = "/__reqaz/reload";
+
+/// The client script injected into served pages. It listens to
+/// the event stream at `RELOAD_PATH`, swapping styles in place
+/// when it can and reloading the page otherwise.
+pub const RELOAD_SCRIPT: &str = r#"(() => {
+    const page = location.pathname + location.search;
+    const events = new EventSource("/__reqaz/reload?page=" + encodeURIComponent(page));
+
+    events.addEventListener("reload", () => location.reload());
+    events.addEventListener("styles", (event) => {
+        const styles = JSON.parse(event.data);
+        const nodes = document.querySelectorAll("style");
+
+        if (nodes.length !== styles.length) {
+            location.reload();
+            return;
+        }
+
+        nodes.forEach((node, idx) => {
+            node.textContent = styles[idx];
+        });
+    });
+})();"#;
+
+/// How many events can queue up for a slow tab before it misses some.
+const CHANNEL_CAPACITY: usize = 16;
+
+/// How many served pages are remembered for stylesheet swaps. Pages
+/// forgotten about get a full reload instead.
+const MAX_PAGES: usize = 256;
+
+/// Pages served most recently, keyed by path and query.
+type PageRegistry = Arc<Mutex<HashMap<String, PageEntry>>>;
+
+/// A served page in the registry.
+#[derive(Default)]
+struct PageEntry {
+    /// The files the page was built from.
+    dependencies: BTreeSet<PathBuf>,
+
+    /// When the page was last served, counting registrations.
+    served: u64
+}
+
+/// An event sent to open pages.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+#[expect(clippy::module_name_repetitions, reason = "a bare `Event` says nothing once imported")]
+pub enum ReloadEvent {
+    /// Something changed, reload the whole page.
+    Reload,
+
+    /// Only stylesheets changed, replace the `style` elements of a
+    /// page with new contents, in document order.
+    Styles {
+        /// The path and query of the page.
+        page: String,
+
+        /// The contents of every `style` element.
+        styles: Vec<String>
+    }
+}
+
+impl ReloadEvent {
+    /// Encode the event as a server-sent event message.
+    #[inline]
+    #[must_use]
+    #[expect(clippy::pattern_type_mismatch, reason = "`styles` is only borrowed to serialize it")]
+    pub fn to_sse(&self) -> String {
+        match self {
+            Self::Reload => "event: reload\ndata:\n\n".to_owned(),
+            Self::Styles { styles, .. } => {
+                // A list of strings always serializes
+                let styles_json = serde_json::to_string(styles).unwrap_or_default();
+
+                format!("event: styles\ndata: {styles_json}\n\n")
+            }
+        }
+    }
+}
+
+/// A live-reload channel, fed by a filesystem watcher over the
+/// served root.
+#[derive(Clone)]
+#[expect(clippy::module_name_repetitions, reason = "the name is the feature's, used across the CLI and README")]
+pub struct LiveReload {
+    /// The watcher feeding the channel, stopped once every clone is dropped.
+    _watcher: Arc<FileWatcher>,
+
+    /// Pages served recently, used to find the pages a stylesheet affects.
+    pages: PageRegistry,
+
+    /// How many pages were registered so far.
+    registrations: Arc<AtomicU64>,
+
+    /// The sending half of the event channel.
+    sender: broadcast::Sender<ReloadEvent>
+}
+
+impl LiveReload {
+    /// Create a server-sent event stream body for a new subscriber,
+    /// viewing the page with the given path and query.
+    #[inline]
+    #[must_use]
+    pub fn event_stream(&self, page: Option<String>) -> ServiceBody {
+        let events = BroadcastStream::new(self.subscribe())
+            .filter_map(move |received| match received {
+                Ok(ReloadEvent::Styles { page: target, .. })
+                    if page.as_ref() != Some(&target) => None,
+                Ok(event) => Some(event),
+                // Lagging behind only means a reload is already on its way
+                Err(_) => None
+            })
+            .map(|event| Ok::<_, BodyError>(Frame::data(Bytes::from(event.to_sse()))));
+
+        StreamBody::new(events).boxed_unsync()
+    }
+
+    /// Remember the files a served page was built from, so that
+    /// stylesheet changes can be pushed to it. Only the most
+    /// recently served pages are kept.
+    #[inline]
+    pub fn register_page(&self, page: String, dependencies: &BTreeSet<PathBuf>) {
+        let canonical = dependencies.iter()
+            .map(|path| path.canonicalize().unwrap_or_else(|_| path.clone()))
+            .collect();
+
+        let served = self.registrations.fetch_add(1, Ordering::Relaxed);
+
+        if let Ok(mut pages) = self.pages.lock() {
+            pages.insert(page, PageEntry { dependencies: canonical, served });
+
+            if pages.len() > MAX_PAGES {
+                let oldest = pages.iter()
+                    .min_by_key(|&(_, entry)| entry.served)
+                    .map(|(oldest_page, _)| oldest_page.clone());
+
+                if let Some(oldest_page) = oldest {
+                    pages.remove(&oldest_page);
+                }
+            }
+        }
+    }
+
+    /// Subscribe to reload events.
+    #[inline]
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<ReloadEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Start watching the resolver root for changes.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the root can't be watched.
+    #[inline]
+    pub fn watch(resolver: SourceResolver) -> Result<Self, NotifyError> {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        let pages = PageRegistry::default();
+
+        let watch_sender = sender.clone();
+        let watch_pages = Arc::clone(&pages);
+        let root = resolver.root.clone();
+
+        let watcher = FileWatcher::new(&root, move |changed| {
+            let events = style_events(&resolver, &watch_pages, &changed)
+                .unwrap_or_else(|| vec![ReloadEvent::Reload]);
+
+            for event in events {
+                // Sending only fails when no pages are open
+                if watch_sender.send(event).is_err() {
+                    break;
+                }
+            }
+        })?;
+
+        Ok(Self {
+            _watcher: Arc::new(watcher),
+            pages,
+            registrations: Arc::default(),
+            sender
+        })
+    }
+}
+
+/// Get the page a subscriber is viewing from the event stream query.
+#[inline]
+#[must_use]
+pub fn page_from_query(query: Option<&str>) -> Option<String> {
+    query?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("page="))
+        .map(|page| percent_decode_str(page).decode_utf8_lossy().into_owned())
+}
+
+/// Build style replacement events for every page depending on the
+/// changed paths. Returns `None` when a full reload is needed instead.
+#[expect(clippy::single_call_fn, reason = "keeps the watcher callback readable")]
+fn style_events(
+    resolver: &SourceResolver,
+    pages: &PageRegistry,
+    changed: &[PathBuf]
+) -> Option<Vec<ReloadEvent>> {
+    // Only stylesheets the fetch mod inlines can be swapped in place
+    let only_styles = changed.iter().all(|path| {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("css") || ext.eq_ignore_ascii_case("scss"))
+    });
+
+    if !only_styles {
+        return None;
+    }
+
+    let dependents: Vec<String> = pages.lock()
+        .ok()?
+        .iter()
+        .filter(|&(_, entry)| changed.iter().any(|path| entry.dependencies.contains(path)))
+        .map(|(page, _)| page.clone())
+        .collect();
+
+    // The stylesheet might be linked directly instead of fetched
+    if dependents.is_empty() {
+        return None;
+    }
+
+    dependents.into_iter()
+        .map(|page| {
+            let uri = Uri::try_from(page.as_str()).ok()?;
+            let source = resolver.resolve_source(&uri).ok()?;
+            let html = String::from_utf8(source.body).ok()?;
+
+            let styles = kuchikiki::parse_html()
+                .one(html)
+                .select("style")
+                .ok()?
+                .map(|element| element.text_contents())
+                .collect();
+
+            Some(ReloadEvent::Styles { page, styles })
+        })
+        .collect()
+}
@@ -1,96 +1,223 @@
 extern crate alloc;
 
+/// Finding pages and assets under a resolver root.
+pub mod discover;
+
+/// Rendering resolver errors as HTML.
+mod error_page;
+
+/// Fetching remote resources for mods.
+mod remote;
+
+/// Compiling SCSS while recording imports.
+mod scss;
+
+use alloc::borrow::Cow;
+use alloc::collections::BTreeSet;
 use alloc::sync::Arc;
 use core::fmt::Display;
 use core::future::Future;
 use core::pin::Pin;
-use crate::html::process_html;
+use crate::caching::{last_modified, CacheControl, Validators};
+use crate::compress::Compression;
+use crate::html::{process_html, ModChains};
+use crate::proxy::Proxy;
+use crate::range::RangeRequest;
 use crate::mediatype::{GetMediaType, TEXT_HTML};
+use crate::reload::{page_from_query, LiveReload, RELOAD_PATH};
 use color_eyre::owo_colors::OwoColorize;
+use core::error::Error;
+use http::header::{HeaderValue, CONTENT_LENGTH, IF_RANGE, RANGE};
 use http::uri::{Authority, InvalidUriParts, Scheme};
-use http_body_util::Full;
-use hyper::{Request, Response, StatusCode, Uri};
-use hyper::body::Bytes;
+use http_body_util::{BodyExt as _, Empty, Full, StreamBody};
+use http_body_util::combinators::UnsyncBoxBody;
+use hyper::{Method, Request, Response, StatusCode, Uri};
+use hyper::body::{Body as _, Bytes, Frame};
 use hyper::body::Incoming as IncomingBody;
 use hyper::service::Service;
 use mediatype::MediaType;
-use rsass::output::Format as RsassFormat;
-use std::io::ErrorKind as IoErrorKind;
-use std::path::{Path, PathBuf};
+use std::io::{Error as IoError, ErrorKind as IoErrorKind, SeekFrom};
+use percent_encoding::percent_decode_str;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs::{metadata, read, File};
+use tokio::io::{AsyncReadExt as _, AsyncSeekExt as _};
+use tokio::task::{spawn_blocking, JoinError};
+use tokio_stream::StreamExt as _;
+use tokio_util::io::ReaderStream;
+
+pub(crate) use self::remote::{fetch_remote, RemoteError};
+use self::remote::Prefetched;
+
+
+/// Files larger than this are always streamed as they are,
+/// instead of read whole to be compressed.
+const MAX_COMPRESSED_FILE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// The methods `SourceService` answers, outside of proxied paths.
+const ALLOWED_METHODS: &str = "GET, HEAD, OPTIONS";
+
+/// How many times mods run again with newly fetched remote
+/// resources, at most.
+const MAX_PREFETCH_ROUNDS: usize = 4;
 
+/// The page served for missing resources, if it exists.
+const NOT_FOUND_PAGE: &str = "/404.html";
+
+/// The error type of `SourceService` response bodies, which can
+/// fail when streamed from elsewhere, like a proxied server.
+pub type BodyError = Box<dyn Error + Send + Sync>;
+
+/// The body type of `SourceService` responses.
+pub type ServiceBody = UnsyncBoxBody<Bytes, BodyError>;
 
 /// The source service, used with hyper
 #[derive(Clone)]
 #[allow(clippy::module_name_repetitions)]
 pub struct SourceService {
-    /// The resolver to use. This is made by the client of
-    /// the library, and is supplied at creation time.
-    resolver: Arc<SourceResolver>,
+    /// The `Cache-Control` headers to send, if any.
+    cache_control: Option<CacheControl>,
+
+    /// How to compress responses for clients that accept it, if enabled.
+    compression: Option<Compression>,
+
+    /// Whether to show mod and parsing errors as an HTML page.
+    error_overlay: bool,
+
+    /// The live-reload channel, if enabled.
+    live_reload: Option<LiveReload>,
 
     /// Whether to log or not
-    log: bool
+    log: bool,
+
+    /// Where to forward requests that shouldn't be resolved, if anywhere.
+    proxy: Option<Proxy>,
+
+    /// The resolver to use. This is made by the client of
+    /// the library, and is supplied at creation time.
+    resolver: Arc<SourceResolver>
 }
 
 
 
 impl SourceService {
-    /// Create a new source service
-    #[inline]
-    pub fn new(resolver: SourceResolver, log: bool) -> Self {
-        Self {
-            resolver: Arc::new(resolver),
-            log
+    /// The `Cache-Control` value for the file a URI is served from, if any.
+    fn cache_control_for(&self, uri: &Uri) -> Option<&str> {
+        let cache_control = self.cache_control.as_ref()?;
+        let path = self.resolver.get_path_from_uri(uri).ok()?;
+        let relative = path.strip_prefix(&self.resolver.root).ok()?;
+
+        cache_control.for_path(relative)
+    }
+
+    /// Build the response for a resolver error.
+    ///
+    /// Missing pages are answered with the project's `404.html`, run
+    /// through the mods, if there is one. With the error overlay
+    /// enabled, mod and parsing problems are shown as an HTML page
+    /// with the whole error chain. Anything else gets a plain text
+    /// description of the error.
+    async fn error_response(&self, err: &ResolverError, uri: &Uri) -> Result<Response<ServiceBody>, ResolverError> {
+        let status = err.status();
+
+        if status == StatusCode::NOT_FOUND {
+            let not_found_page = self.resolver
+                .resolve_source_async(&Uri::from_static(NOT_FOUND_PAGE))
+                .await;
+
+            if let Ok(Resolved { body, mime, .. }) = not_found_page {
+                return Response::builder()
+                    .status(status)
+                    .header("Content-Type", mime.to_string())
+                    .body(Full::new(Bytes::from(body)).map_err(Into::into).boxed_unsync())
+                    .map_err(ResolverError::Http);
+            }
         }
+
+        let (content_type, body) = match *err {
+            ResolverError::ModProblem(_) | ResolverError::ScssCompile(_) if self.error_overlay => (
+                "text/html; charset=utf-8",
+                error_page::overlay(err, uri, self.live_reload.is_some())
+            ),
+            ResolverError::InvalidUriParts(_) |
+            ResolverError::NoMimeFound |
+            ResolverError::NotFound |
+            ResolverError::Forbidden |
+            ResolverError::ServerIssue |
+            ResolverError::WasNotUtf8 |
+            ResolverError::ModProblem(_) |
+            ResolverError::ParseAsMime |
+            ResolverError::ScssCompile(_) |
+            ResolverError::Http(_) |
+            ResolverError::Join(_) => ("text/plain; charset=utf-8", err.to_string())
+        };
+
+        Response::builder()
+            .status(status)
+            .header("Content-Type", content_type)
+            .body(Full::new(Bytes::from(body)).map_err(Into::into).boxed_unsync())
+            .map_err(ResolverError::Http)
     }
 
     /// Handle a hyper request, passed by the service trait
-    #[allow(clippy::unused_async)]
     async fn handle_request(&self, req: Request<IncomingBody>) ->
         // type safety 😌
         Result<<&Self as Service<Request<IncomingBody>>>::Response, <&Self as Service<Request<IncomingBody>>>::Error>
     {
+        if let Some(live_reload) = self.live_reload.as_ref() {
+            if req.uri().path() == RELOAD_PATH && matches!(*req.method(), Method::GET | Method::HEAD) {
+                // HEAD gets the headers without subscribing
+                let body = if req.method() == Method::HEAD {
+                    Empty::new().map_err(Into::into).boxed_unsync()
+                } else {
+                    live_reload.event_stream(page_from_query(req.uri().query()))
+                };
+
+                return Response::builder()
+                    .status(200)
+                    .header("Content-Type", "text/event-stream")
+                    .header("Cache-Control", "no-cache")
+                    .body(body)
+                    .map_err(ResolverError::Http);
+            }
+        }
+
         let req_path = req
             .uri()
             .path_and_query()
             .map(ToString::to_string);
 
-        let source = self.resolver.resolve_source(req.uri());
+        if let Some(proxy) = self.proxy.as_ref() {
+            if let Some(rule) = proxy.rule_for(req.uri()) {
+                let response = proxy.forward(rule, req).await;
 
-        let response = Response::builder();
-        
-        match source {
-            Err(err) => {
-                match err {
-                    ResolverError::NotFound => self.log_source_request(StatusCode::NOT_FOUND, req_path),
-                    ResolverError::ServerIssue |
-                    ResolverError::InvalidUriParts(_) |
-                    ResolverError::NoMimeFound |
-                    ResolverError::WasNotUtf8 |
-                    ResolverError::ModProblem(_) |
-                    ResolverError::ParseAsMime |
-                    ResolverError::Http(_) => self.log_source_request(StatusCode::INTERNAL_SERVER_ERROR, req_path)
-                }
+                self.log_source_request(response.status(), req_path);
 
-                Err(err)
+                return Ok(response);
+            }
+        }
+
+        match *req.method() {
+            Method::GET => self.resolve_request(req, req_path).await,
+            Method::HEAD => self.resolve_request(req, req_path)
+                .await
+                .map(without_body),
+            Method::OPTIONS => {
+                self.log_source_request(StatusCode::NO_CONTENT, req_path);
+
+                Response::builder()
+                    .status(StatusCode::NO_CONTENT)
+                    .header("Allow", ALLOWED_METHODS)
+                    .body(Empty::new().map_err(Into::into).boxed_unsync())
+                    .map_err(ResolverError::Http)
             },
-            Ok(Resolved {
-                body,
-                mime
-            }) => {
-                if self.log {
-                    #[allow(clippy::print_stdout)]
-                    if let Some(path) = req_path {
-                        println!(
-                            "[{}] {}",
-                            200i16.green().bold(),
-                            path
-                        );
-                    }
-                }
+            _ => {
+                self.log_source_request(StatusCode::METHOD_NOT_ALLOWED, req_path);
 
-                response.status(200)
-                    .header("Content-Type", mime.to_string())
-                    .body(Full::new(Bytes::from(body)))
+                Response::builder()
+                    .status(StatusCode::METHOD_NOT_ALLOWED)
+                    .header("Allow", ALLOWED_METHODS)
+                    .header("Content-Type", "text/plain; charset=utf-8")
+                    .body(Full::new(Bytes::from("Method not allowed")).map_err(Into::into).boxed_unsync())
                     .map_err(ResolverError::Http)
             }
         }
@@ -117,10 +244,265 @@ impl SourceService {
             }
         }
     }
+
+    /// Create a new source service
+    #[inline]
+    pub fn new(resolver: SourceResolver, log: bool) -> Self {
+        Self {
+            cache_control: None,
+            compression: None,
+            error_overlay: false,
+            live_reload: None,
+            log,
+            proxy: None,
+            resolver: Arc::new(resolver)
+        }
+    }
+
+    /// Answer a `GET` request from root.
+    async fn resolve_request(&self, req: Request<IncomingBody>, req_path: Option<String>) -> Result<Response<ServiceBody>, ResolverError> {
+        let served = match self.resolver.static_file(req.uri()) {
+            Ok(Some(path)) => Some(self.serve_file(&req, &path).await),
+            Ok(None) => None,
+            Err(err) => Some(Err(err))
+        };
+
+        if let Some(result) = served {
+            return match result {
+                Ok(response) => {
+                    self.log_source_request(response.status(), req_path);
+
+                    Ok(response)
+                },
+                Err(err) => {
+                    self.log_source_request(err.status(), req_path);
+
+                    self.error_response(&err, req.uri()).await
+                }
+            };
+        }
+
+        let source = self.resolver.resolve_source_async(req.uri()).await;
+
+        match source {
+            Err(err) => {
+                self.log_source_request(err.status(), req_path);
+
+                self.error_response(&err, req.uri()).await
+            },
+            Ok(Resolved {
+                mut body,
+                mime,
+                dependencies,
+                remote,
+                ..
+            }) => {
+                if let (Some(live_reload), Some(path)) = (self.live_reload.as_ref(), req_path.as_ref()) {
+                    if mime == TEXT_HTML {
+                        live_reload.register_page(path.clone(), &dependencies);
+                    }
+                }
+
+                // Pages built from remote resources can change without
+                // any file changing, so only the ETag is reliable
+                let modified = if remote.is_empty() {
+                    last_modified(&dependencies).await
+                } else {
+                    None
+                };
+
+                let mut validators = Validators::new(&body, None, modified);
+                let mut response = Response::builder();
+
+                if let Some(compression) = self.compression.as_ref() {
+                    response = response.header("Vary", "Accept-Encoding");
+
+                    let negotiated = req.headers()
+                        .get("Accept-Encoding")
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|accept| compression.negotiate(accept));
+
+                    if let Some(encoding) = negotiated {
+                        if let Ok(Some(compressed)) = compression.compress(encoding, &body) {
+                            validators = Validators::new(&body, Some(encoding), modified);
+                            response = response.header("Content-Encoding", encoding.name());
+                            body = compressed;
+                        }
+                    }
+                }
+
+                response = response.header("ETag", &validators.etag);
+
+                if let Some(date) = validators.last_modified_header() {
+                    response = response.header("Last-Modified", date);
+                }
+
+                if let Some(value) = self.cache_control_for(req.uri()) {
+                    response = response.header("Cache-Control", value);
+                }
+
+                if validators.is_not_modified(req.headers()) {
+                    self.log_source_request(StatusCode::NOT_MODIFIED, req_path);
+
+                    return response.status(StatusCode::NOT_MODIFIED)
+                        .body(Empty::new().map_err(Into::into).boxed_unsync())
+                        .map_err(ResolverError::Http);
+                }
+
+                self.log_source_request(StatusCode::OK, req_path);
+
+                response.status(StatusCode::OK)
+                    .header("Content-Type", mime.to_string())
+                    .body(Full::new(Bytes::from(body)).map_err(Into::into).boxed_unsync())
+                    .map_err(ResolverError::Http)
+            }
+        }
+    }
+
+    /// Serve a file as it is. Small files are compressed in memory
+    /// for clients that accept it. Everything else is streamed from
+    /// disk, honoring `Range` requests.
+    async fn serve_file(&self, req: &Request<IncomingBody>, path: &Path) -> Result<Response<ServiceBody>, ResolverError> {
+        let metadata = metadata(path).await.map_err(read_error)?;
+
+        if metadata.is_dir() {
+            return Err(ResolverError::NotFound);
+        }
+
+        let mime = path.get_media_type().ok_or(ResolverError::NoMimeFound)?;
+        let len = metadata.len();
+        let modified = metadata.modified().ok();
+        let range = req.headers().get(RANGE).and_then(|value| value.to_str().ok());
+
+        let mut response = Response::builder()
+            .header("Content-Type", mime.to_string())
+            .header("Accept-Ranges", "bytes");
+
+        if let Some(value) = self.cache_control_for(req.uri()) {
+            response = response.header("Cache-Control", value);
+        }
+
+        let mut compressed = None;
+
+        if let Some(compression) = self.compression.as_ref() {
+            response = response.header("Vary", "Accept-Encoding");
+
+            let negotiated = req.headers()
+                .get("Accept-Encoding")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|accept| compression.negotiate(accept))
+                .filter(|_| range.is_none() && len <= MAX_COMPRESSED_FILE_SIZE);
+
+            if let Some(encoding) = negotiated {
+                let body = read(path).await.map_err(read_error)?;
+
+                if let Ok(Some(compressed_body)) = compression.compress(encoding, &body) {
+                    compressed = Some((encoding, compressed_body));
+                }
+            }
+        }
+
+        let validators = Validators::for_file(len, modified, compressed.as_ref().map(|&(encoding, _)| encoding));
+
+        response = response.header("ETag", &validators.etag);
+
+        if let Some(date) = validators.last_modified_header() {
+            response = response.header("Last-Modified", date);
+        }
+
+        if validators.is_not_modified(req.headers()) {
+            return response.status(StatusCode::NOT_MODIFIED)
+                .body(Empty::new().map_err(Into::into).boxed_unsync())
+                .map_err(ResolverError::Http);
+        }
+
+        if let Some((encoding, body)) = compressed {
+            return response.status(StatusCode::OK)
+                .header("Content-Encoding", encoding.name())
+                .body(Full::new(Bytes::from(body)).map_err(Into::into).boxed_unsync())
+                .map_err(ResolverError::Http);
+        }
+
+        // A range of an older version of the file is no use, so
+        // the whole file is sent when `If-Range` doesn't match
+        let range_still_valid = req.headers()
+            .get(IF_RANGE)
+            .and_then(|value| value.to_str().ok())
+            .is_none_or(|value| validators.matches_if_range(value));
+
+        let requested = range
+            .filter(|_| range_still_valid)
+            .map_or(RangeRequest::Full, |value| RangeRequest::parse(value, len));
+
+        match requested {
+            RangeRequest::Partial(byte_range) => response.status(StatusCode::PARTIAL_CONTENT)
+                .header("Content-Range", byte_range.content_range(len))
+                .header("Content-Length", byte_range.len())
+                .body(file_body(path, byte_range.start, byte_range.len()).await?)
+                .map_err(ResolverError::Http),
+            RangeRequest::Unsatisfiable => response.status(StatusCode::RANGE_NOT_SATISFIABLE)
+                .header("Content-Range", format!("bytes */{len}"))
+                .body(Empty::new().map_err(Into::into).boxed_unsync())
+                .map_err(ResolverError::Http),
+            RangeRequest::Full => response.status(StatusCode::OK)
+                .header("Content-Length", len)
+                .body(file_body(path, 0, len).await?)
+                .map_err(ResolverError::Http)
+        }
+    }
+
+    /// Send `Cache-Control` headers on resolved responses, picked
+    /// by the path of the file served.
+    #[inline]
+    #[must_use]
+    pub fn with_cache_control(mut self, cache_control: CacheControl) -> Self {
+        self.cache_control = Some(cache_control);
+        self
+    }
+
+    /// Compress responses with the first encoding a request's
+    /// `Accept-Encoding` allows, the same way a CDN serving
+    /// precompressed files would. Every response is compressed as
+    /// it's served, so the quality is capped to keep that quick.
+    #[inline]
+    #[must_use]
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression.on_the_fly());
+        self
+    }
+
+    /// Show mod and parsing errors in the browser as an HTML page
+    /// with the whole error chain, instead of a plain text message.
+    #[inline]
+    #[must_use]
+    pub const fn with_error_overlay(mut self) -> Self {
+        self.error_overlay = true;
+        self
+    }
+
+    /// Enable live reloading, serving the event stream and
+    /// injecting the client script into served pages.
+    #[inline]
+    #[must_use]
+    pub fn with_live_reload(mut self, live_reload: LiveReload) -> Self {
+        Arc::make_mut(&mut self.resolver).live_reload = true;
+        self.live_reload = Some(live_reload);
+
+        self
+    }
+
+    /// Forward requests matching the proxy's rules to other servers,
+    /// instead of resolving them from root.
+    #[inline]
+    #[must_use]
+    pub fn with_proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
 }
 
 impl<'me> Service<Request<IncomingBody>> for &'me SourceService {
-    type Response = Response<Full<Bytes>>;
+    type Response = Response<ServiceBody>;
     type Error = ResolverError;
     type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'me>>;
 
@@ -130,29 +512,190 @@ impl<'me> Service<Request<IncomingBody>> for &'me SourceService {
     }
 }
 
+/// An owned service, for connections that need `'static` futures,
+/// like HTTP/2 ones that spawn a task per stream.
+impl Service<Request<IncomingBody>> for SourceService {
+    type Response = Response<ServiceBody>;
+    type Error = ResolverError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    #[inline]
+    fn call(&self, req: Request<IncomingBody>) -> Self::Future {
+        let service = self.clone();
+
+        Box::pin(async move {
+            service.handle_request(req).await
+        })
+    }
+}
+
 /// Resolver for `SourceService`
 #[derive(Clone)]
 #[non_exhaustive]
 #[allow(clippy::module_name_repetitions)]
+#[expect(clippy::partial_pub_fields, reason = "what the resolver works out for itself isn't configuration")]
 pub struct SourceResolver {
-    /// Root to serve from
-    pub root: PathBuf,
-
     /// URL being served from, used to base path fetches
-    pub authority: Authority
+    pub authority: Authority,
+
+    /// The root the resolver was created with, and the same with
+    /// symlinks resolved, if it existed then.
+    canonical_root: Option<(PathBuf, PathBuf)>,
+
+    /// Whether symlinks under root can point outside of it.
+    pub follow_symlinks: bool,
+
+    /// Whether to inject the live-reload client script into pages.
+    pub live_reload: bool,
+
+    /// The mods to run on pages.
+    pub mods: ModChains,
+
+    /// Remote resources the async resolver fetched for mods, if
+    /// resolving for it.
+    prefetched: Option<Prefetched>,
+
+    /// Whether to serve files exactly as they are, without running
+    /// mods or compiling SCSS.
+    pub raw: bool,
+
+    /// Root to serve from.
+    pub root: PathBuf
 }
 
 impl SourceResolver {
-    /// Create a source resolver
+    /// Get the path under root a URI is read from. The path is
+    /// percent-decoded and its `.` and `..` segments resolved.
+    /// Folders resolve to their `index.html` if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Errors with `Forbidden` if the path leads outside of root,
+    /// including through a symlink, unless those are followed.
+    #[inline]
+    pub fn get_path_from_uri(&self, uri: &Uri) -> Result<PathBuf, ResolverError> {
+        let decoded = percent_decode_str(uri.path()).decode_utf8_lossy();
+        let mut segments: Vec<&str> = vec![];
+
+        for segment in decoded.split('/') {
+            match segment {
+                "" | "." => {},
+                ".." => {
+                    segments.pop().ok_or(ResolverError::Forbidden)?;
+                },
+                // Only single file or folder names, and not something
+                // like a separator or a drive prefix
+                _ => {
+                    let mut components = Path::new(segment).components();
+
+                    let is_plain = !segment.contains(['\\', '\0'])
+                        && matches!((components.next(), components.next()), (Some(Component::Normal(_)), None));
+
+                    if !is_plain {
+                        return Err(ResolverError::Forbidden);
+                    }
+
+                    segments.push(segment);
+                }
+            }
+        }
+
+        let path = segments.iter().fold(self.root.clone(), |path, segment| path.join(segment));
+
+        let pages_path = path.join("index.html");
+
+        #[allow(clippy::absolute_paths)]
+        let resolved = if pages_path.exists() {
+            pages_path
+        } else {
+            path
+        };
+
+        if !self.is_allowed(&resolved) {
+            return Err(ResolverError::Forbidden);
+        }
+
+        Ok(resolved)
+    }
+
+    /// Whether a path can be read: it stays under root once its
+    /// `.` and `..` components are resolved, and inside root
+    /// through symlinks too, unless those are followed.
+    fn is_allowed(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+
+        let mut depth: usize = 0;
+
+        for component in relative.components() {
+            match component {
+                Component::Normal(_) => depth = depth.saturating_add(1),
+                Component::CurDir => {},
+                Component::ParentDir => {
+                    let Some(parent_depth) = depth.checked_sub(1) else {
+                        return false;
+                    };
+
+                    depth = parent_depth;
+                },
+                Component::RootDir | Component::Prefix(_) => return false
+            }
+        }
+
+        self.follow_symlinks || self.is_inside_root(path)
+    }
+
+    /// Whether a path under root is still inside it once symlinks
+    /// are resolved. Paths that don't exist are checked by their
+    /// closest existing parent. Nothing is inside a root that
+    /// doesn't exist.
+    fn is_inside_root(&self, path: &Path) -> bool {
+        // A root created after the resolver, or changed since, is
+        // only looked up then
+        let Some(root) = self.canonical_root.as_ref()
+            .filter(|roots| roots.0 == self.root)
+            .map(|roots| Cow::Borrowed(roots.1.as_path()))
+            .or_else(|| self.root.canonicalize().ok().map(Cow::Owned))
+        else {
+            return false;
+        };
+
+        path.ancestors()
+            .find_map(|ancestor| ancestor.canonicalize().ok())
+            .is_some_and(|canonical| canonical.starts_with(&root))
+    }
+
+    /// Whether a URI read from a path is run through mods or
+    /// compiled before it's served.
+    fn is_processed(&self, uri: &Uri, path: &Path) -> bool {
+        let is_scss = Path::new(uri.path())
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("scss"));
+
+        !self.raw && (is_scss || path.get_media_type() == Some(TEXT_HTML))
+    }
+
+    /// Create a source resolver, running the default mods on pages.
     #[inline]
-    pub const fn new(root: PathBuf, authority: Authority) -> Self {
+    pub fn new(root: PathBuf, authority: Authority) -> Self {
         Self {
-            root,
-            authority
+            authority,
+            canonical_root: root.canonicalize().ok().map(|canonical| (root.clone(), canonical)),
+            follow_symlinks: false,
+            live_reload: false,
+            mods: ModChains::default(),
+            prefetched: None,
+            raw: false,
+            root
         }
     }
 
-    /// Resolve source content from request object from URI
+    /// Resolve source content from request object from URI.
+    ///
+    /// This blocks on reading files and fetching remote resources,
+    /// so async code should use `resolve_source_async` instead, or
+    /// call it from the blocking thread pool.
     /// 
     /// # Errors
     /// 
@@ -165,17 +708,12 @@ impl SourceResolver {
         parts.scheme = Some(Scheme::HTTP);
         parts.authority = Some(self.authority.clone());
 
-        let path = self.get_path_from_uri(uri);
+        let path = self.get_path_from_uri(uri)?;
 
         #[allow(clippy::absolute_paths)]
         let src_mime_uri_fallible = std::fs::read(&path)
-            .map_err(|err| {
-                #[allow(clippy::wildcard_enum_match_arm)]
-                match err.kind() {
-                    IoErrorKind::NotFound => ResolverError::NotFound,
-                    _ => ResolverError::ServerIssue
-                }
-            }).and_then(|src |{
+            .map_err(read_error)
+            .and_then(|src |{
                 path.get_media_type()
                     .clone()
                     .ok_or(ResolverError::NoMimeFound)
@@ -188,8 +726,10 @@ impl SourceResolver {
         
         match src_mime_uri_fallible {
             Ok((src, mime, uri_new)) => {
-                let body = {
-                    if mime == TEXT_HTML {
+                let body_deps = {
+                    if self.raw {
+                        Ok((src, BTreeSet::new(), BTreeSet::new(), vec![]))
+                    } else if mime == TEXT_HTML {
                         let body_str_fallible = String::from_utf8(src)
                             .map_err(|_err| ResolverError::WasNotUtf8);
 
@@ -199,43 +739,135 @@ impl SourceResolver {
                                     .map_err(ResolverError::ModProblem)
                             },
                             Err(err) => Err(err)
-                        }.map(|new_body| new_body.bytes().collect())
+                        }.map(|processed| (
+                            processed.html.into_bytes(),
+                            processed.dependencies,
+                            processed.remote,
+                            processed.warnings
+                        ))
                     } else if Path::new(uri.path())
                         .extension()
                         .map_or(false, |ext| ext.eq_ignore_ascii_case("scss")) {
-                        rsass::compile_scss(&src, RsassFormat::default())
-                            .map_err(|_err| ResolverError::ParseAsMime)
+                        scss::compile(self, &path, &src)
+                            .map_err(|err| ResolverError::ScssCompile(err.into()))
+                            .map(|compiled| (compiled.css, compiled.imports, BTreeSet::new(), vec![]))
                     } else {
-                        Ok(src)
+                        Ok((src, BTreeSet::new(), BTreeSet::new(), vec![]))
                     }
                 };
 
-                body.map(|body_vec| Resolved {
-                    body: body_vec,
-                    mime
+                body_deps.map(|(body_vec, mut dependencies, remote, warnings)| {
+                    dependencies.insert(path);
+
+                    Resolved {
+                        body: body_vec,
+                        mime,
+                        dependencies,
+                        remote,
+                        warnings
+                    }
                 })
             },
             Err(err) => Err(err)
         }
     }
 
-    /// Get the path for a resource request
-    fn get_path_from_uri(&self, uri: &Uri) -> PathBuf {
-        let pathname = uri.path()
-            .get(1..)
-            .unwrap_or("")
-            .to_owned();
+    /// Resolve source content without blocking the async runtime.
+    ///
+    /// Plain files are read with async IO. Pages and SCSS, which run
+    /// mods and compile, are resolved on the blocking thread pool
+    /// instead. Mods never wait on the network there: remote
+    /// resources they ask for are fetched with an async client, and
+    /// the mods run again once they're in, so a slow remote
+    /// component only holds up the request that needs it.
+    ///
+    /// # Errors
+    ///
+    /// Any errors that occur while resolving the URI are propogated
+    #[inline]
+    pub async fn resolve_source_async(&self, uri: &Uri) -> Result<Resolved, ResolverError> {
+        let path = self.get_path_from_uri(uri)?;
 
-        let path = self.root.join(pathname);
-        
-        let pages_path = path.join("index.html");
+        if self.is_processed(uri, &path) {
+            let prefetched = Prefetched::default();
+            let mut resolver = self.clone();
+            resolver.prefetched = Some(prefetched.clone());
 
-        #[allow(clippy::absolute_paths)]
-        if pages_path.exists() {
-            pages_path
-        } else {
-            path
+            // Fetched components can ask for more remote resources,
+            // so this goes one level of nesting per round
+            for _ in 0..MAX_PREFETCH_ROUNDS {
+                let blocking_resolver = resolver.clone();
+                let blocking_uri = uri.clone();
+
+                let result = spawn_blocking(move || blocking_resolver.resolve_source(&blocking_uri))
+                    .await
+                    .map_err(ResolverError::Join)?;
+
+                let missing = prefetched.take_missing();
+
+                if missing.is_empty() {
+                    return result;
+                }
+
+                prefetched.fetch_all(missing).await;
+            }
+
+            let blocking_uri = uri.clone();
+
+            return spawn_blocking(move || resolver.resolve_source(&blocking_uri))
+                .await
+                .map_err(ResolverError::Join)?;
         }
+
+        let body = read(&path).await.map_err(read_error)?;
+        let mime = path.get_media_type().ok_or(ResolverError::NoMimeFound)?;
+
+        Ok(Resolved {
+            body,
+            mime,
+            dependencies: BTreeSet::from([path]),
+            remote: BTreeSet::new(),
+            warnings: vec![]
+        })
+    }
+
+    /// The file a URI is served from as it is, without any
+    /// processing, if it's served that way. The file might not exist.
+    ///
+    /// # Errors
+    ///
+    /// Errors with `Forbidden` if the path leads outside of root.
+    #[inline]
+    pub fn static_file(&self, uri: &Uri) -> Result<Option<PathBuf>, ResolverError> {
+        let path = self.get_path_from_uri(uri)?;
+
+        Ok((!self.is_processed(uri, &path)).then_some(path))
+    }
+
+    /// Choose whether symlinks under root can point outside of it.
+    /// Symlinks staying inside root are always followed.
+    #[inline]
+    #[must_use]
+    pub const fn with_follow_symlinks(mut self, follow_symlinks: bool) -> Self {
+        self.follow_symlinks = follow_symlinks;
+        self
+    }
+
+    /// Choose which mods run on pages.
+    #[inline]
+    #[must_use]
+    pub fn with_mods(mut self, mods: ModChains) -> Self {
+        self.mods = mods;
+        self
+    }
+
+    /// Serve files exactly as they are on disk, like a plain static
+    /// file server would.
+    #[inline]
+    #[must_use]
+    pub const fn with_raw(mut self) -> Self {
+        self.raw = true;
+        self
     }
 }
 
@@ -245,17 +877,68 @@ pub struct Resolved {
     /// The body of the resolved resource, as bytes
     pub body: Vec<u8>,
 
+    /// Every file read to produce the resource, including itself.
+    pub dependencies: BTreeSet<PathBuf>,
+
     /// The mime type
-    pub mime: MediaType<'static>
+    pub mime: MediaType<'static>,
+
+    /// Every remote URL fetched to produce the resource.
+    pub remote: BTreeSet<String>,
+
+    /// Problems that didn't stop the resource from resolving,
+    /// like fetches that failed.
+    pub warnings: Vec<String>
+}
+
+/// Drop the body of a response, for `HEAD` requests, keeping its
+/// `Content-Length` when the body's size is known.
+#[expect(clippy::single_call_fn, reason = "passed to `map` for `HEAD` requests in `handle_request`")]
+fn without_body(response: Response<ServiceBody>) -> Response<ServiceBody> {
+    let (mut parts, body) = response.into_parts();
+
+    if let Some(len) = body.size_hint().exact() {
+        parts.headers.entry(CONTENT_LENGTH).or_insert_with(|| HeaderValue::from(len));
+    }
+
+    Response::from_parts(parts, Empty::new().map_err(Into::into).boxed_unsync())
+}
+
+/// Stream `len` bytes of a file, starting at `start`.
+async fn file_body(path: &Path, start: u64, len: u64) -> Result<ServiceBody, ResolverError> {
+    let mut file = File::open(path).await.map_err(read_error)?;
+    file.seek(SeekFrom::Start(start)).await.map_err(read_error)?;
+
+    let chunks = ReaderStream::new(file.take(len))
+        .map(|chunk| chunk.map(Frame::data).map_err(Into::into));
+
+    Ok(StreamBody::new(chunks).boxed_unsync())
+}
+
+/// The resolver error for a file that couldn't be read. Folders
+/// without an `index.html` count as missing.
+#[expect(clippy::needless_pass_by_value, reason = "taken by value so it can be passed to `map_err`")]
+fn read_error(err: IoError) -> ResolverError {
+    #[expect(clippy::wildcard_enum_match_arm, reason = "every other IO error is on the server's side")]
+    match err.kind() {
+        IoErrorKind::NotFound | IoErrorKind::IsADirectory => ResolverError::NotFound,
+        _ => ResolverError::ServerIssue
+    }
 }
 
 /// Any error that can be returned by the source resolver
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum ResolverError {
+    /// The path leads outside of root.
+    Forbidden,
+
     /// Could not properly construct a URI
     InvalidUriParts(InvalidUriParts),
 
+    /// The blocking task resolving the source panicked.
+    Join(JoinError),
+
     /// Mime could not be found (did you try to resolve a folder without an index.html?)
     NoMimeFound,
 
@@ -271,16 +954,56 @@ pub enum ResolverError {
     /// There was an error running a mod
     ModProblem(eyre::Report),
 
-    /// There was a problem parsing an expected mime type
+    /// There was a problem parsing an expected mime type.
     ParseAsMime,
 
+    /// SCSS failed to compile.
+    ScssCompile(eyre::Report),
+
     /// HTTP problems
     Http(http::Error)
 }
 
+impl ResolverError {
+    /// The HTTP status a response for the error should have.
+    #[inline]
+    #[must_use]
+    pub const fn status(&self) -> StatusCode {
+        match *self {
+            Self::NotFound | Self::NoMimeFound => StatusCode::NOT_FOUND,
+            Self::Forbidden => StatusCode::FORBIDDEN,
+            Self::InvalidUriParts(_) |
+            Self::ServerIssue |
+            Self::WasNotUtf8 |
+            Self::ModProblem(_) |
+            Self::ParseAsMime |
+            Self::ScssCompile(_) |
+            Self::Http(_) |
+            Self::Join(_) => StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
 #[allow(clippy::missing_trait_methods)]
 #[allow(clippy::absolute_paths)]
-impl std::error::Error for ResolverError {}
+impl Error for ResolverError {
+    #[inline]
+    #[expect(clippy::pattern_type_mismatch, reason = "causes are handed out as borrows of `self`")]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::InvalidUriParts(iup) => iup.source(),
+            Self::ModProblem(err) | Self::ScssCompile(err) => Some(err.as_ref()),
+            Self::Http(err) => err.source(),
+            Self::Join(err) => Some(err),
+            Self::NoMimeFound |
+            Self::NotFound |
+            Self::Forbidden |
+            Self::ServerIssue |
+            Self::WasNotUtf8 |
+            Self::ParseAsMime => None
+        }
+    }
+}
 
 #[allow(clippy::absolute_paths)]
 #[allow(clippy::pattern_type_mismatch)]
@@ -291,11 +1014,14 @@ impl Display for ResolverError {
             Self::InvalidUriParts(iup) => iup.fmt(formatter),
             Self::NoMimeFound => formatter.write_str("No mime found (did you try to resolve a folder without an index.html?)"),
             Self::NotFound => formatter.write_str("Resource not found"),
+            Self::Forbidden => formatter.write_str("Resource is outside of root"),
             Self::ServerIssue => formatter.write_str("There was a server issue"),
             Self::WasNotUtf8 => formatter.write_str("Expected UTF8, but resource contents were not"),
             Self::ModProblem(err) => formatter.write_fmt(format_args!("There was a mod problem: {err}")),
             Self::ParseAsMime => formatter.write_str("There was a problem parsing an expected mime type"),
-            Self::Http(err) => err.fmt(formatter)
+            Self::ScssCompile(err) => formatter.write_fmt(format_args!("SCSS failed to compile: {err}")),
+            Self::Http(err) => err.fmt(formatter),
+            Self::Join(err) => formatter.write_fmt(format_args!("Resolving failed: {err}"))
         }
     }
 }
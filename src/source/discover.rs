@@ -0,0 +1,160 @@
+use super::SourceResolver;
+use hyper::Uri;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use std::io::Error as IoError;
+use std::path::{Component, Path, PathBuf};
+use walkdir::{DirEntry, WalkDir};
+
+
+/// Characters escaped when turning a file path into a URI path.
+const PATH_ESCAPES: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// A page found under the resolver root.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Page {
+    /// The path of the page, relative to the root.
+    pub path: PathBuf,
+
+    /// The URI the page is served at.
+    pub uri: Uri
+}
+
+/// A non-page file found under the resolver root, like an
+/// image or stylesheet.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Asset {
+    /// The path of the asset, relative to the root.
+    pub path: PathBuf,
+
+    /// The URI the asset is served at.
+    pub uri: Uri
+}
+
+impl SourceResolver {
+    /// Find every asset under the root.
+    ///
+    /// Assets are all files that aren't pages, skipping the same
+    /// partial and hidden entries as `discover_pages`. Assets are
+    /// sorted by path.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the root can't be walked.
+    #[inline]
+    pub fn discover_assets(&self) -> Result<Vec<Asset>, IoError> {
+        let mut assets = vec![];
+
+        for walked in self.walk_root() {
+            let entry = walked.map_err(IoError::from)?;
+
+            let Ok(path) = entry.path().strip_prefix(&self.root) else {
+                continue;
+            };
+
+            if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("html")) {
+                continue;
+            }
+
+            let Ok(uri) = Uri::try_from(format!("/{}", uri_path_from(path))) else {
+                continue;
+            };
+
+            assets.push(Asset {
+                uri,
+                path: path.to_path_buf()
+            });
+        }
+
+        Ok(assets)
+    }
+
+    /// Find every page under the root.
+    ///
+    /// Pages are `*.html` files, where `index.html` is served at
+    /// its directory, the same way `resolve_source` looks them up.
+    /// Partial directories and files starting with `_` (like
+    /// `_components`) and hidden ones starting with `.` are skipped.
+    /// Pages are sorted by path.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the root can't be walked.
+    #[inline]
+    pub fn discover_pages(&self) -> Result<Vec<Page>, IoError> {
+        let mut pages = vec![];
+
+        for walked in self.walk_root() {
+            let entry = walked.map_err(IoError::from)?;
+
+            let Ok(path) = entry.path().strip_prefix(&self.root) else {
+                continue;
+            };
+
+            if !path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("html")) {
+                continue;
+            }
+
+            let uri_path = if path.file_name().is_some_and(|name| name == "index.html") {
+                path.parent()
+                    .map(|parent| format!("{}/", uri_path_from(parent)))
+                    .unwrap_or_default()
+            } else {
+                uri_path_from(path)
+            };
+
+            let Ok(uri) = Uri::try_from(format!("/{}", uri_path.trim_start_matches('/'))) else {
+                continue;
+            };
+
+            pages.push(Page {
+                uri,
+                path: path.to_path_buf()
+            });
+        }
+
+        Ok(pages)
+    }
+
+    /// Walk the files under root in a stable order, skipping partials
+    /// and hidden entries.
+    #[expect(clippy::filetype_is_file, reason = "symlinks and special files are skipped too, not only directories")]
+    fn walk_root(&self) -> impl Iterator<Item = walkdir::Result<DirEntry>> {
+        WalkDir::new(&self.root)
+            .sort_by_file_name()
+            .into_iter()
+            // Partials start with `_`, and hidden entries with `.`
+            .filter_entry(|entry| {
+                entry.depth() == 0 || !entry.file_name()
+                    .to_str()
+                    .is_none_or(|name| name.starts_with('_') || name.starts_with('.'))
+            })
+            .filter(|walked| walked.as_ref().map_or(true, |entry| entry.file_type().is_file()))
+    }
+}
+
+/// Build a percent-encoded URI path from a relative file path.
+fn uri_path_from(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(segment) => segment.to_str(),
+            Component::Prefix(_) |
+            Component::RootDir |
+            Component::CurDir |
+            Component::ParentDir => None
+        })
+        .map(|segment| utf8_percent_encode(segment, PATH_ESCAPES).to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
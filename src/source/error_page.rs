@@ -0,0 +1,70 @@
+use super::ResolverError;
+use crate::reload::RELOAD_SCRIPT;
+use core::error::Error as _;
+use html_escape::encode_text;
+use hyper::Uri;
+
+
+/// Styles for the error overlay, kept inline so the page works
+/// without anything else being served.
+const OVERLAY_STYLE: &str = "
+    body { margin: 0; background: #1e1e24; color: #e8e8ee; font: 15px/1.5 system-ui, sans-serif; }
+    main { max-width: 60rem; margin: 0 auto; padding: 3rem 2rem; }
+    h1 { margin: 0 0 0.5rem; color: #ff6b6b; font-size: 1.5rem; }
+    .page { margin: 0 0 2rem; color: #a0a0b0; }
+    ol { margin: 0; padding: 0; list-style: none; }
+    li { margin: 0 0 0.75rem; padding: 0.75rem 1rem; background: #2a2a33; border-left: 3px solid #ff6b6b; white-space: pre-wrap; }
+    li + li { border-left-color: #6b6b80; }
+    code { color: #ffd479; font-family: ui-monospace, monospace; }
+    footer { margin-top: 2rem; color: #a0a0b0; font-size: 0.85rem; }
+";
+
+/// Render an HTML page describing a resolver error, with every
+/// cause in its chain. Names in backticks, like the component or
+/// href that failed, are highlighted.
+#[expect(clippy::single_call_fn, reason = "keeps the page's markup out of `error_response`")]
+pub fn overlay(err: &ResolverError, uri: &Uri, live_reload: bool) -> String {
+    let mut causes = vec![];
+    let mut source = err.source();
+
+    while let Some(cause) = source {
+        causes.push(format!("<li>{}</li>", highlight_code(&cause.to_string())));
+        source = cause.source();
+    }
+
+    let reload_script = if live_reload {
+        format!("<script>{RELOAD_SCRIPT}</script>")
+    } else {
+        String::new()
+    };
+
+    let footer = if live_reload {
+        "This page reloads once a file changes."
+    } else {
+        "Fix the problem, then reload the page."
+    };
+
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>reqaz: {title}</title><style>{OVERLAY_STYLE}</style></head>\
+         <body><main><h1>{title}</h1><p class=\"page\">While resolving <code>{page}</code></p>\
+         <ol>{causes}</ol><footer>{footer}</footer></main>{reload_script}</body></html>",
+        title = encode_text(&err.to_string()),
+        page = encode_text(&uri.to_string()),
+        causes = causes.join("")
+    )
+}
+
+/// Escape a message, wrapping anything in backticks in `<code>`.
+#[expect(clippy::single_call_fn, reason = "escaping is a step of its own, apart from the page layout")]
+fn highlight_code(message: &str) -> String {
+    message.split('`')
+        .enumerate()
+        .map(|(idx, part)| {
+            if idx & 1 == 1 {
+                format!("<code>{}</code>", encode_text(part))
+            } else {
+                encode_text(part).into_owned()
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,255 @@
+extern crate alloc;
+
+use alloc::collections::BTreeSet;
+use alloc::sync::Arc;
+use core::error::Error;
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use core::mem::take;
+use crate::mediatype::APPLICATION_OCTET_STREAM;
+use super::SourceResolver;
+use http::header::{CONTENT_TYPE, LOCATION};
+use http_body_util::{BodyExt as _, Empty};
+use hyper::body::Bytes;
+use hyper::Uri;
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use mediatype::MediaTypeBuf;
+use std::collections::HashMap;
+use std::io::{Error as IoError, Read as _};
+use std::sync::{Mutex, OnceLock};
+use tokio::task::JoinSet;
+
+
+/// How many redirects a remote fetch follows.
+const MAX_REDIRECTS: usize = 5;
+
+/// The async client remote resources are fetched with.
+type RemoteClient = Client<HttpsConnector<HttpConnector>, Empty<Bytes>>;
+
+/// A remote resource fetched over HTTP.
+#[derive(Clone)]
+pub struct RemoteResponse {
+    /// The bytes of the response body.
+    pub body: Vec<u8>,
+
+    /// The mime type, from `Content-Type`.
+    pub mime: MediaTypeBuf
+}
+
+/// Remote resources fetched ahead of time with an async client, so
+/// mods running on the blocking pool never wait on the network.
+#[derive(Clone, Default)]
+pub struct Prefetched {
+    /// URLs mods asked for that haven't been fetched yet.
+    missing: Arc<Mutex<BTreeSet<String>>>,
+
+    /// Responses, or why fetching failed, keyed by URL.
+    responses: Arc<Mutex<HashMap<String, Result<RemoteResponse, String>>>>
+}
+
+impl Prefetched {
+    /// Fetch URLs with the async client, all at once.
+    pub async fn fetch_all(&self, urls: BTreeSet<String>) {
+        let mut fetches = JoinSet::new();
+
+        for url in urls {
+            fetches.spawn(async move {
+                let response = fetch_async(&url).await;
+                (url, response)
+            });
+        }
+
+        while let Some(joined) = fetches.join_next().await {
+            if let (Ok((url, response)), Ok(mut responses)) = (joined, self.responses.lock()) {
+                responses.insert(url, response);
+            }
+        }
+    }
+
+    /// The response for a URL, if it was fetched. Otherwise the URL
+    /// is recorded, to be fetched before the mods run again.
+    fn get(&self, url: &str) -> Option<Result<RemoteResponse, String>> {
+        let response = self.responses
+            .lock()
+            .ok()
+            .and_then(|responses| responses.get(url).cloned());
+
+        if response.is_none() {
+            if let Ok(mut missing) = self.missing.lock() {
+                missing.insert(url.to_owned());
+            }
+        }
+
+        response
+    }
+
+    /// Take the URLs mods asked for that haven't been fetched yet.
+    pub fn take_missing(&self) -> BTreeSet<String> {
+        self.missing
+            .lock()
+            .map(|mut missing| take(&mut *missing))
+            .unwrap_or_default()
+    }
+}
+
+/// Errors possible when fetching a remote resource.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RemoteError {
+    /// The async client failed to fetch the resource.
+    Async(String),
+
+    /// There was an IO problem.
+    Io(IoError),
+
+    /// There was a networking problem.
+    ///
+    /// Wrapped in a box for size concerns.
+    Network(Box<ureq::Error>),
+
+    /// The resource hasn't been fetched by the async resolver yet.
+    NotFetched
+}
+
+#[expect(clippy::missing_trait_methods, reason = "the deprecated methods keep their defaults")]
+impl Error for RemoteError {
+    #[expect(clippy::pattern_type_mismatch, reason = "the wrapped errors are borrowed from `self`")]
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Async(_) | Self::NotFetched => None,
+            Self::Io(err) => Some(err),
+            Self::Network(err) => Some(err)
+        }
+    }
+}
+
+impl Display for RemoteError {
+    #[expect(clippy::pattern_type_mismatch, reason = "messages are borrowed from `self`")]
+    #[expect(clippy::renamed_function_params, reason = "spelled out like the crate's other `fmt` impls")]
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Async(message) => formatter.write_str(message),
+            Self::Io(err) => err.fmt(formatter),
+            Self::Network(err) => err.fmt(formatter),
+            Self::NotFetched => formatter.write_str("The resource hasn't been fetched yet")
+        }
+    }
+}
+
+/// Fetch a remote resource for a mod.
+///
+/// Resolvers with prefetched responses never touch the network
+/// here. URLs they don't have yet are recorded and reported as not
+/// fetched, so the async resolver can fetch them and run the mods
+/// again. Other resolvers block on the request.
+///
+/// # Errors
+///
+/// Errors if the request fails, or the resource wasn't prefetched.
+pub fn fetch_remote(resolver: &SourceResolver, uri: &Uri) -> Result<RemoteResponse, RemoteError> {
+    let url = uri.to_string();
+
+    if let Some(prefetched) = resolver.prefetched.as_ref() {
+        return prefetched.get(&url)
+            .map_or(Err(RemoteError::NotFetched), |response| response.map_err(RemoteError::Async));
+    }
+
+    let response = ureq::get(&url)
+        .call()
+        .map_err(|err| RemoteError::Network(Box::new(err)))?;
+
+    let mime = MediaTypeBuf::from_string(response.content_type().to_owned())
+        .unwrap_or_else(|_| APPLICATION_OCTET_STREAM.into());
+
+    let mut body = vec![];
+
+    response.into_reader()
+        .read_to_end(&mut body)
+        .map_err(RemoteError::Io)?;
+
+    Ok(RemoteResponse { body, mime })
+}
+
+/// Fetch a remote resource with the async client, following
+/// redirects. Failures are kept as messages, so they can be shared
+/// between every mod asking for the same URL.
+#[expect(clippy::single_call_fn, reason = "keeps the task `fetch_all` spawns per URL short")]
+async fn fetch_async(url: &str) -> Result<RemoteResponse, String> {
+    let mut uri: Uri = url.parse().map_err(|err| format!("{err}"))?;
+
+    for _ in 0..=MAX_REDIRECTS {
+        let response = client()
+            .get(uri.clone())
+            .await
+            .map_err(|err| format!("{err}"))?;
+
+        let status = response.status();
+
+        if status.is_redirection() {
+            let location = response.headers()
+                .get(LOCATION)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| format!("{status} without a location"))?;
+
+            uri = redirect_uri(&uri, location)?;
+            continue;
+        }
+
+        if !status.is_success() {
+            return Err(format!("{url}: status code {}", status.as_u16()));
+        }
+
+        // Parameters like the charset are dropped, the same way
+        // the blocking client does
+        let mime = response.headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| MediaTypeBuf::from_string(value.to_owned()).ok())
+            .map_or_else(|| APPLICATION_OCTET_STREAM.into(), |full| full.essence().into());
+
+        let body = response.into_body()
+            .collect()
+            .await
+            .map_err(|err| format!("{err}"))?
+            .to_bytes()
+            .to_vec();
+
+        return Ok(RemoteResponse { body, mime });
+    }
+
+    Err(format!("{url}: too many redirects"))
+}
+
+/// Where a redirect leads, resolving locations without a scheme
+/// and host against the URI redirected from.
+#[expect(clippy::single_call_fn, reason = "keeps the redirect loop in `fetch_async` readable")]
+fn redirect_uri(from: &Uri, location: &str) -> Result<Uri, String> {
+    let to: Uri = location.parse().map_err(|err| format!("{err}"))?;
+
+    if to.scheme().is_some() {
+        return Ok(to);
+    }
+
+    let mut parts = from.clone().into_parts();
+    parts.path_and_query = to.path_and_query().cloned();
+
+    Uri::from_parts(parts).map_err(|err| format!("{err}"))
+}
+
+/// The async client, shared by every resolver.
+#[expect(clippy::single_call_fn, reason = "scopes the static holding the client")]
+fn client() -> &'static RemoteClient {
+    static CLIENT: OnceLock<RemoteClient> = OnceLock::new();
+
+    CLIENT.get_or_init(|| {
+        let connector = HttpsConnectorBuilder::new()
+            .with_webpki_roots()
+            .https_or_http()
+            .enable_http1()
+            .build();
+
+        Client::builder(TokioExecutor::new()).build(connector)
+    })
+}
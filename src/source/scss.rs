@@ -0,0 +1,104 @@
+extern crate alloc;
+
+use alloc::collections::BTreeSet;
+use alloc::rc::Rc;
+use core::cell::RefCell;
+use core::fmt::{Debug, Formatter, Result as FmtResult};
+use super::SourceResolver;
+use rsass::input::{Context, LoadError, Loader, SourceFile, SourceName};
+use rsass::output::Format as RsassFormat;
+use std::fs::File;
+use std::io::{Error as IoError, ErrorKind};
+use std::path::{Path, PathBuf};
+
+
+/// SCSS after compiling it to CSS.
+pub struct CompiledScss {
+    /// The resulting CSS.
+    pub css: Vec<u8>,
+
+    /// Every file imported while compiling.
+    pub imports: BTreeSet<PathBuf>
+}
+
+/// An SCSS loader reading from a base directory, keeping track of
+/// every file it opens.
+struct RecordingLoader<'resolver> {
+    /// The directory imports are relative to.
+    base: PathBuf,
+
+    /// Every file opened so far.
+    opened: Rc<RefCell<BTreeSet<PathBuf>>>,
+
+    /// The resolver whose root imports have to stay in.
+    resolver: &'resolver SourceResolver
+}
+
+impl Debug for RecordingLoader<'_> {
+    #[expect(clippy::renamed_function_params, reason = "`formatter` matches the crate's `Display` impls")]
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FmtResult {
+        formatter.debug_struct("RecordingLoader")
+            .field("base", &self.base)
+            .field("opened", &self.opened)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Loader for RecordingLoader<'_> {
+    type File = File;
+
+    fn find_file(&self, url: &str) -> Result<Option<Self::File>, LoadError> {
+        if url.is_empty() {
+            return Ok(None);
+        }
+
+        let full_path = self.base.join(url);
+
+        if !self.resolver.is_allowed(&full_path) {
+            let forbidden = IoError::new(ErrorKind::PermissionDenied, "the import leads outside of root");
+
+            return Err(LoadError::Input(full_path.display().to_string(), forbidden));
+        }
+
+        if !full_path.is_file() {
+            return Ok(None);
+        }
+
+        let file = File::open(&full_path)
+            .map_err(|err| LoadError::Input(full_path.display().to_string(), err))?;
+
+        self.opened.borrow_mut().insert(full_path);
+
+        Ok(Some(file))
+    }
+}
+
+/// Compile an SCSS file at `path` with contents `src`.
+///
+/// Imports are resolved next to the file, and every file that
+/// gets pulled in is recorded. Imports leading outside of the
+/// resolver's root fail, the same as requests for them would.
+#[expect(clippy::single_call_fn, reason = "keeps the loader setup out of `resolve_source`")]
+#[expect(clippy::result_large_err, reason = "rsass's error is passed on as it comes")]
+pub fn compile(resolver: &SourceResolver, path: &Path, src: &[u8]) -> Result<CompiledScss, rsass::Error> {
+    let imports = Rc::new(RefCell::new(BTreeSet::new()));
+
+    let loader = RecordingLoader {
+        base: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        opened: Rc::clone(&imports),
+        resolver
+    };
+
+    let name = path.file_name()
+        .map(|file_name| file_name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let css = Context::for_loader(loader)
+        .with_format(RsassFormat::default())
+        .transform(SourceFile::scss_bytes(src, SourceName::root(name)))?;
+
+    Ok(CompiledScss {
+        css,
+        imports: imports.take()
+    })
+}
@@ -0,0 +1,126 @@
+use core::time::Duration;
+use notify_debouncer_mini::notify::event::{EventKind, MetadataKind, ModifyKind};
+use notify_debouncer_mini::notify::{
+    Config, Error as NotifyError, Event, EventHandler, RecommendedWatcher, RecursiveMode, Watcher, WatcherKind
+};
+use notify_debouncer_mini::{new_debouncer_opt, Config as DebouncerConfig, DebounceEventResult, Debouncer};
+use std::path::{Path, PathBuf};
+
+
+/// How long to wait for a burst of filesystem events to settle
+/// before reporting it. Editors tend to write files in several steps.
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// A recursive filesystem watcher.
+///
+/// Watching stops once this is dropped.
+pub struct FileWatcher {
+    /// The underlying debounced watcher, kept alive for its drop guard.
+    _debouncer: Debouncer<ChangeWatcher>
+}
+
+impl FileWatcher {
+    /// Watch a directory recursively, calling `on_change` from a
+    /// background thread with every batch of changed paths.
+    ///
+    /// The root is canonicalized first, so all reported paths are
+    /// absolute and canonical. Reads are not reported, only
+    /// creations, modifications and removals, and editor swap,
+    /// backup and hidden files are left out.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the root can't be canonicalized or watched.
+    #[inline]
+    pub fn new<F>(root: &Path, mut on_change: F) -> Result<Self, NotifyError>
+    where
+        F: FnMut(Vec<PathBuf>) + Send + 'static
+    {
+        let canonical_root = root.canonicalize()
+            .map_err(NotifyError::io)?;
+
+        let config = DebouncerConfig::default().with_timeout(DEBOUNCE_TIMEOUT);
+
+        let mut debouncer = new_debouncer_opt::<_, ChangeWatcher>(config, move |res: DebounceEventResult| {
+            if let Ok(events) = res {
+                let changed: Vec<PathBuf> = events.into_iter()
+                    .map(|event| event.path)
+                    .filter(|path| !is_ignored(path))
+                    .collect();
+
+                if !changed.is_empty() {
+                    on_change(changed);
+                }
+            }
+        })?;
+
+        debouncer.watcher().watch(&canonical_root, RecursiveMode::Recursive)?;
+
+        Ok(Self {
+            _debouncer: debouncer
+        })
+    }
+}
+
+/// The recommended watcher, passing on only events that change
+/// files. The debouncer can't tell event kinds apart, and without
+/// this, every file served would count as changed once it's opened.
+struct ChangeWatcher {
+    /// The watcher events come from.
+    inner: RecommendedWatcher
+}
+
+#[expect(clippy::missing_trait_methods, reason = "the defaults suit a watcher that only filters events")]
+impl Watcher for ChangeWatcher {
+    #[inline]
+    fn kind() -> WatcherKind {
+        RecommendedWatcher::kind()
+    }
+
+    #[inline]
+    fn new<F: EventHandler>(mut event_handler: F, config: Config) -> Result<Self, NotifyError> {
+        let inner = RecommendedWatcher::new(
+            move |res: Result<Event, NotifyError>| {
+                // Only changes to file contents or layout are kept. Errors
+                // are passed on, for the debouncer to report
+                let keep = res.as_ref().map_or(true, |event| {
+                    !matches!(event.kind, EventKind::Modify(ModifyKind::Metadata(MetadataKind::AccessTime)))
+                        && matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_))
+                });
+
+                if keep {
+                    event_handler.handle_event(res);
+                }
+            },
+            config
+        )?;
+
+        Ok(Self { inner })
+    }
+
+    #[inline]
+    fn unwatch(&mut self, path: &Path) -> Result<(), NotifyError> {
+        self.inner.unwatch(path)
+    }
+
+    #[inline]
+    fn watch(&mut self, path: &Path, recursive_mode: RecursiveMode) -> Result<(), NotifyError> {
+        self.inner.watch(path, recursive_mode)
+    }
+}
+
+/// Whether a changed path should be ignored, like editor swap
+/// and backup files. Vim checks it can write to a folder with a
+/// `4913` file, and Emacs autosaves to `#name#`.
+#[inline]
+#[must_use]
+pub fn is_ignored(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| {
+            name.starts_with('.')
+                || name.ends_with('~')
+                || (name.len() > 1 && name.starts_with('#') && name.ends_with('#'))
+                || name == "4913"
+        })
+}
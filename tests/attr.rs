@@ -0,0 +1,27 @@
+use hyper::Uri;
+use reqaz::html::attr::Href;
+
+#[test]
+fn href_kinds() {
+    assert!(matches!(Href::try_from("/logo.png"), Ok(Href::Absolute(_))));
+    assert!(matches!(Href::try_from("logo.png"), Ok(Href::Relative(_))));
+    assert!(matches!(Href::try_from("https://example.com/"), Ok(Href::Uri(_))));
+
+    for other in ["javascript:void(0)", "mailto:hi@example.com", "#top", "?page=2", "//example.com/logo.png"] {
+        assert!(matches!(Href::try_from(other), Ok(Href::Other(_))), "{other}");
+    }
+}
+
+#[test]
+fn href_relative_to_page_folder() {
+    let page: Uri = "/blog/post.html".parse().unwrap();
+    let folder: Uri = "/blog/".parse().unwrap();
+
+    let from_page = Href::try_from("logo.png").unwrap().append_to_uri(&page).unwrap();
+    let from_folder = Href::try_from("logo.png").unwrap().append_to_uri(&folder).unwrap();
+    let absolute = Href::try_from("/logo.png").unwrap().append_to_uri(&page).unwrap();
+
+    assert_eq!(from_page.path(), "/blog/logo.png");
+    assert_eq!(from_folder.path(), "/blog/logo.png");
+    assert_eq!(absolute.path(), "/logo.png");
+}
@@ -0,0 +1,43 @@
+use http::header::{HeaderMap, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH};
+use reqaz::caching::{CacheControl, Validators};
+use reqaz::compress::Encoding;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+#[test]
+fn validators_not_modified() {
+    let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let validators = Validators::new(b"<p>Hello</p>", None, Some(modified));
+    let compressed = Validators::new(b"<p>Hello</p>", Some(Encoding::Gzip), Some(modified));
+
+    assert_ne!(validators.etag, compressed.etag);
+
+    let mut headers = HeaderMap::new();
+    assert!(!validators.is_not_modified(&headers));
+
+    headers.insert(IF_NONE_MATCH, HeaderValue::from_str(&format!("\"other\", W/{}", validators.etag)).unwrap());
+    assert!(validators.is_not_modified(&headers));
+    assert!(!compressed.is_not_modified(&headers));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(IF_MODIFIED_SINCE, HeaderValue::from_str(&validators.last_modified_header().unwrap()).unwrap());
+    assert!(validators.is_not_modified(&headers));
+
+    let later = Validators::new(b"<p>Hello</p>", None, Some(modified + Duration::from_secs(5)));
+    assert!(!later.is_not_modified(&headers));
+}
+
+#[test]
+fn cache_control_first_match_wins() {
+    let cache_control = CacheControl::new([
+        ("assets/**".to_owned(), "public, max-age=31536000, immutable".to_owned()),
+        ("**/*.css".to_owned(), "public, max-age=60".to_owned())
+    ])
+        .unwrap()
+        .with_fallback("no-cache".to_owned());
+
+    assert_eq!(cache_control.for_path(Path::new("assets/site.css")), Some("public, max-age=31536000, immutable"));
+    assert_eq!(cache_control.for_path(Path::new("styles/site.css")), Some("public, max-age=60"));
+    assert_eq!(cache_control.for_path(Path::new("index.html")), Some("no-cache"));
+    assert_eq!(CacheControl::default().for_path(Path::new("index.html")), None);
+}
@@ -0,0 +1,209 @@
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::fs::{create_dir_all, read, remove_dir_all, write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Create a site with a `reqaz.json` and files under `web`, starting
+/// from an empty folder
+fn site(name: &str, config: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("cli").join(name);
+
+    let _ = remove_dir_all(&dir);
+    create_dir_all(dir.join("web")).unwrap();
+    write(dir.join("reqaz.json"), config).unwrap();
+
+    for &(path, contents) in files {
+        let file = dir.join("web").join(path);
+
+        create_dir_all(file.parent().unwrap()).unwrap();
+        write(file, contents).unwrap();
+    }
+
+    dir
+}
+
+/// Run the CLI in a site
+fn reqaz(site: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_reqaz"))
+        .args(args)
+        .current_dir(site)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn build_reports_in_pipeline_order() {
+    let pipelines = (0..8)
+        .map(|idx| format!(r#"{{ "input": "/missing-{idx}.html", "output": "{idx}.html" }}"#))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let config = format!(r#"{{ "root": "web", "generate": {{ "output_dir": "out", "pipelines": [{pipelines}] }} }}"#);
+    let dir = site("report_order", &config, &[]);
+
+    let mut reports = vec![];
+
+    for _ in 0..3 {
+        let output = reqaz(&dir, &["build", "--keep-going", "-j", "8"]);
+        assert!(!output.status.success());
+
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        let failed: Vec<String> = stderr.lines()
+            .filter(|line| line.contains("error:"))
+            .map(|line| line.split_whitespace().find(|word| word.starts_with("/missing-")).unwrap().to_owned())
+            .collect();
+
+        let expected: Vec<String> = (0..8).map(|idx| format!("/missing-{idx}.html")).collect();
+        assert_eq!(failed, expected);
+
+        reports.push(stderr);
+    }
+
+    assert!(reports.windows(2).all(|pair| pair[0] == pair[1]));
+}
+
+#[test]
+fn build_manifest_describes_outputs() {
+    let config = r#"{ "root": "web", "generate": { "output_dir": "out", "pages": "auto", "assets": {} } }"#;
+    let dir = site("manifest", config, &[
+        ("index.html", "<html><head></head><body><p>Home</p></body></html>"),
+        ("style.css", "p { color: red; }")
+    ]);
+
+    assert!(reqaz(&dir, &["build"]).status.success());
+
+    let manifest: Value = serde_json::from_slice(&read(dir.join("out/reqaz-manifest.json")).unwrap()).unwrap();
+    let outputs = manifest["outputs"].as_array().unwrap();
+
+    for (output, mime) in [("index.html", "text/html"), ("style.css", "text/css")] {
+        let entry = outputs.iter()
+            .find(|entry| entry["output"] == output)
+            .unwrap_or_else(|| panic!("{output} missing from the manifest"));
+        let bytes = read(dir.join("out").join(output)).unwrap();
+        let hash: String = Sha256::digest(&bytes).iter().map(|byte| format!("{byte:02x}")).collect();
+
+        assert_eq!(entry["size"], bytes.len() as u64, "{output}");
+        assert_eq!(entry["hash"], hash.as_str(), "{output}");
+        assert_eq!(entry["mime"], mime, "{output}");
+    }
+}
+
+#[test]
+fn clean_only_removes_stale_outputs() {
+    let both = r#"{ "root": "web", "generate": { "output_dir": "out", "pipelines": [
+        { "input": "/a.html", "output": "a.html" },
+        { "input": "/b.html", "output": "nested/b.html" }
+    ] } }"#;
+    let dir = site("clean", both, &[
+        ("a.html", "<html><body>A</body></html>"),
+        ("b.html", "<html><body>B</body></html>")
+    ]);
+
+    assert!(reqaz(&dir, &["build"]).status.success());
+    write(dir.join("out/notes.txt"), "placed by hand").unwrap();
+
+    let only_a = r#"{ "root": "web", "generate": { "output_dir": "out", "pipelines": [
+        { "input": "/a.html", "output": "a.html" }
+    ] } }"#;
+    write(dir.join("reqaz.json"), only_a).unwrap();
+
+    let dry_run = reqaz(&dir, &["clean", "--dry-run"]);
+    assert!(dry_run.status.success());
+    assert!(String::from_utf8(dry_run.stdout).unwrap().contains("Would remove nested/b.html"));
+    assert!(dir.join("out/nested/b.html").exists());
+
+    assert!(reqaz(&dir, &["clean"]).status.success());
+    assert!(!dir.join("out/nested").exists());
+    assert!(dir.join("out/a.html").exists());
+    assert!(dir.join("out/notes.txt").exists());
+
+    assert!(reqaz(&dir, &["clean", "--all"]).status.success());
+    assert!(!dir.join("out/a.html").exists());
+    assert!(dir.join("out/notes.txt").exists());
+}
+
+#[test]
+fn diff_lists_changes() {
+    let before = r#"{ "root": "web", "generate": { "output_dir": "out", "pipelines": [
+        { "input": "/a.html", "output": "a.html" },
+        { "input": "/b.html", "output": "b.html" },
+        { "input": "/same.html", "output": "same.html" }
+    ] } }"#;
+    let dir = site("diff", before, &[
+        ("a.html", "<html><body>A</body></html>"),
+        ("b.html", "<html><body>B</body></html>"),
+        ("c.html", "<html><body>C</body></html>"),
+        ("same.html", "<html><body>Same</body></html>")
+    ]);
+
+    assert!(reqaz(&dir, &["build"]).status.success());
+
+    let after = r#"{ "root": "web", "generate": { "output_dir": "out", "pipelines": [
+        { "input": "/a.html", "output": "a.html" },
+        { "input": "/c.html", "output": "c.html" },
+        { "input": "/same.html", "output": "same.html" }
+    ] } }"#;
+    write(dir.join("reqaz.json"), after).unwrap();
+    write(dir.join("web/a.html"), "<html><body>A, changed</body></html>").unwrap();
+
+    let output = reqaz(&dir, &["diff"]);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let listed = |label: &str, file: &str| stdout.lines().any(|line| line.contains(label) && line.ends_with(file));
+
+    assert!(listed("added", "c.html"), "{stdout}");
+    assert!(listed("changed", "a.html"), "{stdout}");
+    assert!(listed("removed", "b.html"), "{stdout}");
+    assert!(!stdout.contains("same.html"), "{stdout}");
+    assert!(stdout.contains("+<html><head></head><body>A, changed</body></html>"), "{stdout}");
+    assert!(stdout.contains("1 added, 1 changed, 1 removed, 1 unchanged."), "{stdout}");
+
+    assert!(!dir.join("out/c.html").exists());
+    assert!(dir.join("out/b.html").exists());
+}
+
+#[test]
+fn fingerprint_keeps_fixed_names() {
+    let config = r#"{ "root": "web", "generate": { "output_dir": "out", "pages": "auto", "assets": {}, "fingerprint": {} } }"#;
+    let dir = site("fingerprint_fixed", config, &[
+        ("index.html", r#"<html><head><link rel="icon" href="/favicon.ico"></head><body><img src="/logo.png"></body></html>"#),
+        ("logo.png", "not really a png"),
+        ("favicon.ico", "not really an icon"),
+        ("robots.txt", "User-agent: *")
+    ]);
+
+    assert!(reqaz(&dir, &["build"]).status.success());
+
+    assert_eq!(read(dir.join("out/robots.txt")).unwrap(), b"User-agent: *");
+    assert!(dir.join("out/favicon.ico").is_file());
+    assert!(!dir.join("out/logo.png").exists());
+
+    let index = String::from_utf8(read(dir.join("out/index.html")).unwrap()).unwrap();
+    assert!(index.contains(r#"href="/favicon.ico""#), "{index}");
+    assert!(!index.contains(r#"src="/logo.png""#), "{index}");
+}
+
+#[test]
+fn fingerprint_rebuilds_only_referencing_outputs() {
+    let config = r#"{ "root": "web", "generate": { "output_dir": "out", "pages": "auto", "assets": {}, "fingerprint": {} } }"#;
+    let dir = site("fingerprint_rebuild", config, &[
+        ("index.html", r#"<html><head><link rel="stylesheet" href="/a.css"></head><body></body></html>"#),
+        ("other.html", r#"<html><head><link rel="stylesheet" href="/b.css"></head><body></body></html>"#),
+        ("a.css", "p { color: red; }"),
+        ("b.css", "p { color: blue; }"),
+        ("notes.txt", "Plain")
+    ]);
+
+    let first = reqaz(&dir, &["build"]);
+    assert!(first.status.success());
+    assert!(String::from_utf8(first.stdout).unwrap().contains("Built 5 outputs."));
+
+    write(dir.join("web/b.css"), "p { color: green; }").unwrap();
+
+    let second = reqaz(&dir, &["build"]);
+    let stdout = String::from_utf8(second.stdout).unwrap();
+
+    assert!(second.status.success());
+    assert!(stdout.contains("Built 2 outputs, 3 up to date"), "{stdout}");
+}
@@ -1,5 +1,17 @@
+// Each test target uses only some of these helpers
+#![allow(dead_code)]
+
+use core::net::SocketAddr;
+use http_body_util::{BodyExt, Empty};
+use hyper::body::Bytes;
+use hyper::server::conn::http1;
+use hyper::{Method, Request, Response};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::{TokioExecutor, TokioIo};
 use kuchikiki::traits::TendrilSink;
+use reqaz::source::SourceService;
 use std::path::PathBuf;
+use tokio::net::TcpListener;
 
 pub fn serve_dir() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("e2e/web")
@@ -16,3 +28,35 @@ pub fn parse_html_string(s: &str) -> reqaz::html::Html {
 pub fn without_newlines(s: &str) -> String {
     s.replace('\n', "").to_string()
 }
+
+/// Serve a service over HTTP/1.1 on a free local port
+pub async fn spawn_service(service: SourceService) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(http1::Builder::new().serve_connection(TokioIo::new(stream), service.clone()));
+        }
+    });
+
+    addr
+}
+
+/// Send a request to a served service, collecting the whole body
+pub async fn send(addr: SocketAddr, method: Method, path: &str, headers: &[(&str, &str)]) -> Response<Bytes> {
+    let client = Client::builder(TokioExecutor::new()).build_http::<Empty<Bytes>>();
+
+    let mut request = Request::builder()
+        .method(method)
+        .uri(format!("http://{addr}{path}"));
+
+    for &(name, value) in headers {
+        request = request.header(name, value);
+    }
+
+    let response = client.request(request.body(Empty::new()).unwrap()).await.unwrap();
+    let (parts, body) = response.into_parts();
+
+    Response::from_parts(parts, body.collect().await.unwrap().to_bytes())
+}
@@ -0,0 +1,53 @@
+use flate2::read::GzDecoder;
+use reqaz::compress::{Compression, Encoding};
+use std::io::Read;
+
+#[test]
+fn negotiate_encoding() {
+    let compression = Compression::default();
+
+    assert_eq!(compression.negotiate("gzip, deflate, br"), Some(Encoding::Brotli));
+    assert_eq!(compression.negotiate("gzip, br;q=0"), Some(Encoding::Gzip));
+    assert_eq!(compression.negotiate("*"), Some(Encoding::Brotli));
+    assert_eq!(compression.negotiate("identity"), None);
+
+    let gzip_only = Compression::new(vec![Encoding::Gzip]);
+
+    assert_eq!(gzip_only.negotiate("br"), None);
+}
+
+#[test]
+fn compress_skips_small_bodies() {
+    let compression = Compression::default().with_min_size(64);
+    let body = "<p>Hello world!</p>\n".repeat(16);
+
+    assert!(compression.compress(Encoding::Gzip, b"tiny").unwrap().is_none());
+
+    let compressed = compression.compress(Encoding::Gzip, body.as_bytes())
+        .unwrap()
+        .expect("body should be compressed");
+
+    let mut decompressed = String::new();
+    GzDecoder::new(compressed.as_slice()).read_to_string(&mut decompressed).unwrap();
+
+    assert_eq!(decompressed, body);
+}
+
+#[test]
+fn compress_on_the_fly_caps_quality() {
+    let body = "<p>Hello world!</p>\n".repeat(64);
+
+    let on_the_fly = Compression::default().on_the_fly();
+    let brotli = on_the_fly.compress(Encoding::Brotli, body.as_bytes()).unwrap().unwrap();
+    let gzip = on_the_fly.compress(Encoding::Gzip, body.as_bytes()).unwrap().unwrap();
+
+    assert_eq!(brotli, Encoding::Brotli.compress(body.as_bytes(), Some(4)).unwrap());
+    assert_eq!(gzip, Encoding::Gzip.compress(body.as_bytes(), Some(6)).unwrap());
+
+    let low = Compression::default().with_quality(Some(1)).on_the_fly();
+
+    assert_eq!(
+        low.compress(Encoding::Brotli, body.as_bytes()).unwrap().unwrap(),
+        Encoding::Brotli.compress(body.as_bytes(), Some(1)).unwrap()
+    );
+}
@@ -0,0 +1,52 @@
+use reqaz::source::SourceResolver;
+use std::path::PathBuf;
+
+mod common;
+
+#[test]
+fn discover_pages() {
+    let resolver = SourceResolver::new(common::serve_dir(), "reqaz.local".try_into().unwrap());
+
+    let pages = resolver.discover_pages().unwrap();
+
+    let found: Vec<_> = pages
+        .iter()
+        .map(|page| (page.uri.to_string(), page.path.clone()))
+        .collect();
+
+    assert!(found.contains(&("/".to_owned(), PathBuf::from("index.html"))));
+    assert!(found.contains(&(
+        "/component/with_props.html".to_owned(),
+        PathBuf::from("component/with_props.html")
+    )));
+    assert!(!found
+        .iter()
+        .any(|(_, path)| path.starts_with("_components")));
+}
+
+#[test]
+fn discovered_pages_resolve() {
+    let resolver = SourceResolver::new(common::serve_dir(), "reqaz.local".try_into().unwrap());
+
+    for page in resolver.discover_pages().unwrap() {
+        assert!(resolver.resolve_source(&page.uri).is_ok(), "{}", page.uri);
+    }
+}
+
+#[test]
+fn discover_assets() {
+    let resolver = SourceResolver::new(common::serve_dir(), "reqaz.local".try_into().unwrap());
+
+    let assets: Vec<_> = resolver
+        .discover_assets()
+        .unwrap()
+        .into_iter()
+        .map(|asset| asset.path)
+        .collect();
+
+    assert!(assets.contains(&PathBuf::from("robots.txt")));
+    assert!(!assets.iter().any(|path| path.starts_with("_styles")));
+    assert!(!assets
+        .iter()
+        .any(|path| path.extension().is_some_and(|ext| ext == "html")));
+}
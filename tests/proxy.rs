@@ -0,0 +1,47 @@
+use http::header::{HeaderMap, HeaderValue};
+use hyper::Uri;
+use reqaz::proxy::{remove_hop_by_hop, target_uri, Proxy};
+
+#[test]
+fn proxy_target_uri() {
+    let target = |base: &'static str, uri: &'static str| {
+        target_uri(&Uri::from_static(base), &Uri::from_static(uri)).unwrap().to_string()
+    };
+
+    assert_eq!(target("http://localhost:8080", "/api/users?page=2"), "http://localhost:8080/api/users?page=2");
+    assert_eq!(target("http://localhost:8080/v1/", "/api/users?page=2"), "http://localhost:8080/v1/api/users?page=2");
+    assert_eq!(target("http://localhost:8080/v1", "/api"), "http://localhost:8080/v1/api");
+}
+
+#[test]
+fn proxy_removes_hop_by_hop() {
+    let mut headers = HeaderMap::new();
+
+    headers.insert("connection", HeaderValue::from_static("keep-alive, x-session-hop"));
+    headers.insert("keep-alive", HeaderValue::from_static("timeout=5"));
+    headers.insert("x-session-hop", HeaderValue::from_static("1"));
+    headers.insert("transfer-encoding", HeaderValue::from_static("chunked"));
+    headers.insert("content-type", HeaderValue::from_static("application/json"));
+    headers.insert("x-request-id", HeaderValue::from_static("42"));
+
+    remove_hop_by_hop(&mut headers);
+
+    let mut left = headers.keys().map(|name| name.as_str()).collect::<Vec<_>>();
+    left.sort_unstable();
+
+    assert_eq!(left, ["content-type", "x-request-id"]);
+}
+
+#[test]
+fn proxy_longest_prefix_wins() {
+    let proxy = Proxy::new([
+        ("/api/".to_owned(), Uri::from_static("http://localhost:8080")),
+        ("/api/admin/".to_owned(), Uri::from_static("http://localhost:9090"))
+    ]);
+
+    let target = |uri| proxy.rule_for(&Uri::from_static(uri)).map(|rule| rule.target.to_string());
+
+    assert_eq!(target("/api/admin/users").as_deref(), Some("http://localhost:9090/"));
+    assert_eq!(target("/api/users").as_deref(), Some("http://localhost:8080/"));
+    assert_eq!(target("/index.html"), None);
+}
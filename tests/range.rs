@@ -0,0 +1,28 @@
+use reqaz::range::RangeRequest;
+
+#[test]
+fn range_parse() {
+    let partial = |header| match RangeRequest::parse(header, 1000) {
+        RangeRequest::Partial(range) => Some((range.start, range.end, range.len())),
+        _ => None
+    };
+
+    assert_eq!(partial("bytes=0-99"), Some((0, 99, 100)));
+    assert_eq!(partial("bytes=900-"), Some((900, 999, 100)));
+    assert_eq!(partial("bytes=-100"), Some((900, 999, 100)));
+    assert_eq!(partial("bytes=-5000"), Some((0, 999, 1000)));
+    assert_eq!(partial("bytes=990-2000"), Some((990, 999, 10)));
+
+    assert_eq!(RangeRequest::parse("bytes=1000-", 1000), RangeRequest::Unsatisfiable);
+    assert_eq!(RangeRequest::parse("bytes=-0", 1000), RangeRequest::Unsatisfiable);
+    assert_eq!(RangeRequest::parse("bytes=0-1,5-9", 1000), RangeRequest::Full);
+    assert_eq!(RangeRequest::parse("bytes=50-10", 1000), RangeRequest::Full);
+    assert_eq!(RangeRequest::parse("items=0-1", 1000), RangeRequest::Full);
+
+    let RangeRequest::Partial(first_byte) = RangeRequest::parse("bytes=0-0", 1000) else {
+        panic!("bytes=0-0 should be a partial range");
+    };
+
+    assert_eq!(first_byte.len(), 1);
+    assert!(!first_byte.is_empty());
+}
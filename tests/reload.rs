@@ -0,0 +1,22 @@
+use reqaz::reload::{page_from_query, ReloadEvent};
+
+#[test]
+fn reload_styles_event_is_json() {
+    let event = ReloadEvent::Styles {
+        page: "/index.html".to_owned(),
+        styles: vec!["h1 { content: \"\\2014\"; }\n".to_owned(), "p {}".to_owned()]
+    };
+
+    assert_eq!(
+        event.to_sse(),
+        "event: styles\ndata: [\"h1 { content: \\\"\\\\2014\\\"; }\\n\",\"p {}\"]\n\n"
+    );
+    assert_eq!(ReloadEvent::Reload.to_sse(), "event: reload\ndata:\n\n");
+}
+
+#[test]
+fn reload_page_from_query() {
+    assert_eq!(page_from_query(Some("page=%2Fproduct.html%3Fid%3D1")).as_deref(), Some("/product.html?id=1"));
+    assert_eq!(page_from_query(Some("other=1")), None);
+    assert_eq!(page_from_query(None), None);
+}
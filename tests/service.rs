@@ -0,0 +1,161 @@
+use hyper::header::{ALLOW, CONTENT_LENGTH, CONTENT_RANGE, CONTENT_TYPE, ETAG};
+use hyper::{Method, StatusCode};
+use reqaz::reload::{LiveReload, RELOAD_PATH};
+use reqaz::source::{SourceResolver, SourceService};
+use std::fs::{create_dir_all, metadata, read, write};
+use std::path::PathBuf;
+
+mod common;
+
+fn service() -> SourceService {
+    let resolver = SourceResolver::new(common::serve_dir(), "reqaz.local".try_into().unwrap());
+
+    SourceService::new(resolver, false)
+}
+
+#[tokio::test]
+async fn service_head_keeps_length() {
+    let addr = common::spawn_service(service()).await;
+
+    let get = common::send(addr, Method::GET, "/basic.html", &[]).await;
+    let head = common::send(addr, Method::HEAD, "/basic.html", &[]).await;
+
+    assert_eq!(head.status(), StatusCode::OK);
+    assert_eq!(head.headers()[CONTENT_TYPE], get.headers()[CONTENT_TYPE]);
+    assert_eq!(head.headers()[CONTENT_LENGTH], get.body().len().to_string().as_str());
+    assert!(head.body().is_empty());
+    assert!(!get.body().is_empty());
+}
+
+#[tokio::test]
+async fn service_options_lists_methods() {
+    let addr = common::spawn_service(service()).await;
+
+    let response = common::send(addr, Method::OPTIONS, "/basic.html", &[]).await;
+
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(response.headers()[ALLOW], "GET, HEAD, OPTIONS");
+    assert!(response.body().is_empty());
+}
+
+#[tokio::test]
+async fn service_rejects_other_methods() {
+    let addr = common::spawn_service(service()).await;
+
+    for method in [Method::POST, Method::PUT, Method::DELETE] {
+        let response = common::send(addr, method.clone(), "/basic.html", &[]).await;
+
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED, "{method}");
+        assert_eq!(response.headers()[ALLOW], "GET, HEAD, OPTIONS", "{method}");
+    }
+}
+
+#[tokio::test]
+async fn service_head_reload_stream() {
+    let resolver = SourceResolver::new(common::serve_dir(), "reqaz.local".try_into().unwrap());
+    let live_reload = LiveReload::watch(resolver.clone()).unwrap();
+    let addr = common::spawn_service(SourceService::new(resolver, false).with_live_reload(live_reload)).await;
+
+    let response = common::send(addr, Method::HEAD, RELOAD_PATH, &[]).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[CONTENT_TYPE], "text/event-stream");
+    assert!(response.body().is_empty());
+}
+
+#[tokio::test]
+async fn service_range_partial() {
+    let addr = common::spawn_service(service()).await;
+    let robots = read(common::serve_dir().join("robots.txt")).unwrap();
+
+    let response = common::send(addr, Method::GET, "/robots.txt", &[("Range", "bytes=2-6")]).await;
+
+    assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(response.headers()[CONTENT_RANGE], format!("bytes 2-6/{}", robots.len()).as_str());
+    assert_eq!(response.headers()[CONTENT_LENGTH], "5");
+    assert_eq!(response.body().as_ref(), &robots[2..=6]);
+}
+
+#[tokio::test]
+async fn service_range_unsatisfiable() {
+    let addr = common::spawn_service(service()).await;
+    let len = metadata(common::serve_dir().join("robots.txt")).unwrap().len();
+
+    let response = common::send(addr, Method::GET, "/robots.txt", &[("Range", &format!("bytes={len}-"))]).await;
+
+    assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+    assert_eq!(response.headers()[CONTENT_RANGE], format!("bytes */{len}").as_str());
+    assert!(response.body().is_empty());
+}
+
+#[tokio::test]
+async fn service_range_if_range() {
+    let addr = common::spawn_service(service()).await;
+    let robots = read(common::serve_dir().join("robots.txt")).unwrap();
+
+    let stale = common::send(addr, Method::GET, "/robots.txt", &[("Range", "bytes=2-6"), ("If-Range", "\"stale\"")]).await;
+
+    assert_eq!(stale.status(), StatusCode::OK);
+    assert_eq!(stale.body().as_ref(), robots.as_slice());
+
+    let etag = stale.headers()[ETAG].to_str().unwrap().to_owned();
+    let current = common::send(addr, Method::GET, "/robots.txt", &[("Range", "bytes=2-6"), ("If-Range", &etag)]).await;
+
+    assert_eq!(current.status(), StatusCode::PARTIAL_CONTENT);
+    assert_eq!(current.body().as_ref(), &robots[2..=6]);
+}
+
+#[tokio::test]
+async fn service_not_found_page() {
+    let root = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("not_found_root");
+    create_dir_all(&root).unwrap();
+    write(root.join("404.html"), "<html><body><p>Nothing here</p></body></html>").unwrap();
+
+    let resolver = SourceResolver::new(root, "reqaz.local".try_into().unwrap());
+    let addr = common::spawn_service(SourceService::new(resolver, false)).await;
+
+    let response = common::send(addr, Method::GET, "/missing.html", &[]).await;
+    let body = String::from_utf8(response.body().to_vec()).unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    assert!(response.headers()[CONTENT_TYPE].to_str().unwrap().starts_with("text/html"));
+    assert!(body.contains("<p>Nothing here</p>"), "{body}");
+}
+
+#[tokio::test]
+async fn service_overlay_escapes_errors() {
+    let root = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("overlay_root");
+    create_dir_all(&root).unwrap();
+    write(
+        root.join("index.html"),
+        r#"<html><body><object data="/<b>missing</b>.html" nib-mod="component"></object></body></html>"#
+    ).unwrap();
+
+    let resolver = SourceResolver::new(root, "reqaz.local".try_into().unwrap());
+    let addr = common::spawn_service(SourceService::new(resolver, false).with_error_overlay()).await;
+
+    let response = common::send(addr, Method::GET, "/", &[]).await;
+    let body = String::from_utf8(response.body().to_vec()).unwrap();
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(body.contains("&lt;b&gt;missing&lt;/b&gt;"), "{body}");
+    assert!(!body.contains("<b>missing</b>"), "{body}");
+}
+
+#[tokio::test]
+async fn service_raw_skips_mods() {
+    let resolver = SourceResolver::new(common::serve_dir(), "reqaz.local".try_into().unwrap()).with_raw();
+    let addr = common::spawn_service(SourceService::new(resolver, false)).await;
+
+    let page = common::send(addr, Method::GET, "/component/with_props.html", &[]).await;
+
+    assert_eq!(page.status(), StatusCode::OK);
+    assert!(page.headers()[CONTENT_TYPE].to_str().unwrap().starts_with("text/html"));
+    assert_eq!(page.body().as_ref(), read(common::serve_dir().join("component/with_props.html")).unwrap());
+
+    let style = common::send(addr, Method::GET, "/_styles/fetch_css.css", &[]).await;
+
+    assert_eq!(style.status(), StatusCode::OK);
+    assert!(style.headers()[CONTENT_TYPE].to_str().unwrap().starts_with("text/css"));
+    assert_eq!(style.body().as_ref(), read(common::serve_dir().join("_styles/fetch_css.css")).unwrap());
+}
@@ -1,6 +1,11 @@
-use reqaz::source::SourceResolver;
-use std::fs::File;
+use core::error::Error;
+use reqaz::html::ModChains;
+use reqaz::source::{ResolverError, SourceResolver};
+use std::fs::{create_dir_all, write, File};
 use std::io::Read;
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
 
 mod common;
 
@@ -48,5 +53,250 @@ source_test_list![
     [component_with_props, "component/with_props.html"],
     [title_propped, "component/title_propped.html"],
     [component_slotted, "component/with_slots.html"],
-    [comp_has_comp, "component/comp_has_comp.html"]
+    [comp_has_comp, "component/comp_has_comp.html"],
+    [component_object_param, "component/object_param.html"]
 ];
+
+#[test]
+fn source_live_reload_script_injected_once() {
+    let mut resolver = SourceResolver::new(common::serve_dir(), "reqaz.local".try_into().unwrap());
+    resolver.live_reload = true;
+
+    let out = resolver
+        .resolve_source(&"/component/comp_has_comp.html".try_into().unwrap())
+        .unwrap();
+
+    let html = String::from_utf8(out.body).unwrap();
+
+    assert_eq!(html.matches("data-reqaz-reload").count(), 1);
+}
+
+#[test]
+fn source_dependencies_recorded() {
+    let serve_dir = common::serve_dir();
+    let resolver = SourceResolver::new(serve_dir.clone(), "reqaz.local".try_into().unwrap());
+
+    let out = resolver
+        .resolve_source(&"/component/comp_has_comp.html".try_into().unwrap())
+        .unwrap();
+
+    assert!(out.dependencies.contains(&serve_dir.join("component/comp_has_comp.html")));
+    assert!(out.dependencies.contains(&serve_dir.join("_components/List.html")));
+    assert!(out.dependencies.contains(&serve_dir.join("_components/H1.html")));
+}
+
+#[test]
+fn source_scss_imports_recorded() {
+    let serve_dir = common::serve_dir();
+    let resolver = SourceResolver::new(serve_dir.clone(), "reqaz.local".try_into().unwrap());
+
+    let out = resolver
+        .resolve_source(&"/_styles/theme.scss".try_into().unwrap())
+        .unwrap();
+
+    assert!(String::from_utf8(out.body).unwrap().contains("#336699"));
+    assert!(out.dependencies.contains(&serve_dir.join("_styles/theme.scss")));
+    assert!(out.dependencies.contains(&serve_dir.join("_styles/_colors.scss")));
+}
+
+#[test]
+fn source_scss_imports_stay_in_root() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("scss_outside_root");
+    let root = dir.join("root");
+    create_dir_all(&root).unwrap();
+    write(dir.join("_secret.scss"), "$secret: #123456;").unwrap();
+    write(root.join("relative.scss"), "@import \"../secret\";\np { color: $secret; }").unwrap();
+    write(
+        root.join("absolute.scss"),
+        format!("@import \"{}\";\np {{ color: $secret; }}", dir.join("secret").display())
+    ).unwrap();
+
+    let resolver = SourceResolver::new(root.clone(), "reqaz.local".try_into().unwrap());
+
+    for path in ["/relative.scss", "/absolute.scss"] {
+        let Err(err) = resolver.resolve_source(&path.try_into().unwrap()) else {
+            panic!("{path} should fail to compile");
+        };
+
+        assert!(matches!(err, ResolverError::ScssCompile(_)), "{path}");
+        assert!(err.source().is_some(), "{path}");
+        assert!(resolver.clone().with_follow_symlinks(true).resolve_source(&path.try_into().unwrap()).is_err(), "{path}");
+    }
+}
+
+#[test]
+fn source_failed_fetch_warns() {
+    let serve_dir = common::serve_dir();
+    let resolver = SourceResolver::new(serve_dir.clone(), "reqaz.local".try_into().unwrap());
+
+    let out = resolver
+        .resolve_source(&"/fetch_missing.html".try_into().unwrap())
+        .unwrap();
+
+    assert_eq!(out.warnings.len(), 1);
+    assert!(out.warnings[0].contains("/_styles/missing.css"));
+    assert!(out.dependencies.contains(&serve_dir.join("_styles/missing.css")));
+}
+
+#[test]
+fn source_custom_mod_chain() {
+    let serve_dir = common::serve_dir();
+    let resolver = SourceResolver::new(serve_dir, "reqaz.local".try_into().unwrap())
+        .with_mods(ModChains::new(vec!["fetch".to_owned(), "css".to_owned()]).with_dir(
+            "component".into(),
+            vec!["fetch".to_owned()]
+        ));
+
+    let minified = resolver
+        .resolve_source(&"/fetch_css.html".try_into().unwrap())
+        .unwrap();
+
+    assert!(String::from_utf8(minified.body).unwrap().contains("h1{color:red}"));
+
+    let chains = &resolver.mods;
+
+    assert_eq!(chains.for_uri(&"/component/with_props.html".try_into().unwrap()), ["fetch"]);
+    assert_eq!(chains.for_uri(&"/components.html".try_into().unwrap()), ["fetch", "css"]);
+
+    let unprocessed = resolver
+        .with_mods(ModChains::new(vec!["fetch".to_owned()]))
+        .resolve_source(&"/fetch_css.html".try_into().unwrap())
+        .unwrap();
+
+    assert!(String::from_utf8(unprocessed.body).unwrap().contains("color: red;"));
+}
+
+#[test]
+fn source_query_props() {
+    let resolver = SourceResolver::new(common::serve_dir(), "reqaz.local".try_into().unwrap());
+
+    let out = resolver
+        .resolve_source(&"/product.html?id=42&text=Hello+there".try_into().unwrap())
+        .unwrap();
+
+    let body = common::without_newlines(&String::from_utf8(out.body).unwrap());
+
+    assert!(body.contains("<title>Product 42</title>"));
+    assert!(body.contains("<p>Product 42</p>"));
+    assert!(body.contains(r#"<param name="color">"#));
+    assert!(body.contains("<h1>Hello there</h1>"));
+}
+
+#[tokio::test]
+async fn source_async_matches_blocking() {
+    let resolver = SourceResolver::new(common::serve_dir(), "reqaz.local".try_into().unwrap());
+
+    for path in ["/component/comp_has_comp.html", "/_styles/theme.scss", "/_styles/fetch_css.css"] {
+        let uri = path.try_into().unwrap();
+
+        let blocking = resolver.resolve_source(&uri).unwrap();
+        let non_blocking = resolver.resolve_source_async(&uri).await.unwrap();
+
+        assert_eq!(blocking.body, non_blocking.body, "{path}");
+        assert_eq!(blocking.mime, non_blocking.mime, "{path}");
+        assert_eq!(blocking.dependencies, non_blocking.dependencies, "{path}");
+    }
+
+    assert!(matches!(
+        resolver.resolve_source_async(&"/missing.png".try_into().unwrap()).await,
+        Err(ResolverError::NotFound)
+    ));
+}
+
+#[test]
+fn source_error_status() {
+    let resolver = SourceResolver::new(common::serve_dir(), "reqaz.local".try_into().unwrap());
+
+    for path in ["/missing.html", "/_styles/"] {
+        let Err(err) = resolver.resolve_source(&path.try_into().unwrap()) else {
+            panic!("{path} should fail to resolve");
+        };
+
+        assert_eq!(err.status(), 404, "{path}");
+    }
+}
+
+#[test]
+fn source_path_traversal_forbidden() {
+    let resolver = SourceResolver::new(common::serve_dir(), "reqaz.local".try_into().unwrap());
+
+    for path in ["/../Cargo.toml", "/component/../../Cargo.toml", "/%2e%2e/Cargo.toml", "/component/%2E%2E%2F%2E%2E/Cargo.toml"] {
+        let Err(err) = resolver.resolve_source(&path.try_into().unwrap()) else {
+            panic!("{path} should be forbidden");
+        };
+
+        assert!(matches!(err, ResolverError::Forbidden), "{path}");
+        assert_eq!(err.status(), 403, "{path}");
+    }
+
+    let normalized = resolver.get_path_from_uri(&"/component/./../basic%2Ehtml".try_into().unwrap()).unwrap();
+
+    assert_eq!(normalized, common::serve_dir().join("basic.html"));
+
+    let missing_root = SourceResolver::new(common::serve_dir().join("missing"), "reqaz.local".try_into().unwrap());
+    let missing = missing_root.get_path_from_uri(&"/basic.html".try_into().unwrap());
+
+    assert!(matches!(missing, Err(ResolverError::Forbidden)));
+}
+
+#[test]
+fn source_changed_root_checked() {
+    let mut resolver = SourceResolver::new(common::serve_dir().join("component"), "reqaz.local".try_into().unwrap());
+    resolver.root = common::serve_dir();
+
+    let path = resolver.get_path_from_uri(&"/basic.html".try_into().unwrap()).unwrap();
+
+    assert_eq!(path, common::serve_dir().join("basic.html"));
+
+    resolver.root = common::serve_dir().join("component");
+
+    let component = resolver.get_path_from_uri(&"/with_props.html".try_into().unwrap()).unwrap();
+
+    assert_eq!(component, common::serve_dir().join("component/with_props.html"));
+}
+
+#[tokio::test]
+async fn source_async_fetches_remote_components() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let origin = format!("http://{}", listener.local_addr().unwrap());
+    let component = format!(r#"<div><link nib-mod="fetch" href="{origin}/remote.css"><p>From remote</p></div>"#);
+
+    let served_component = component.clone();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0; 1024];
+            let read = stream.read(&mut request).await.unwrap();
+
+            let (mime, body) = if String::from_utf8_lossy(&request[..read]).starts_with("GET /remote.css") {
+                ("text/css", ".remote { color: red; }".to_owned())
+            } else {
+                ("text/html; charset=utf-8", served_component.clone())
+            };
+
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: {mime}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+
+    let root = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("remote_root");
+    create_dir_all(&root).unwrap();
+    write(
+        root.join("index.html"),
+        format!(r#"<html><body><object data="{origin}/component.html" nib-mod="component"></object></body></html>"#)
+    ).unwrap();
+
+    let resolver = SourceResolver::new(root, "reqaz.local".try_into().unwrap());
+    let out = resolver.resolve_source_async(&"/".try_into().unwrap()).await.unwrap();
+    let body = String::from_utf8(out.body).unwrap();
+
+    assert!(body.contains("<p>From remote</p>"), "{body}");
+    assert!(body.contains(".remote{color:red}"), "{body}");
+    assert!(out.warnings.is_empty(), "{:?}", out.warnings);
+    assert!(out.remote.contains(&format!("{origin}/remote.css")));
+}
@@ -0,0 +1,13 @@
+use reqaz::watch::is_ignored;
+use std::path::Path;
+
+#[test]
+fn watch_ignores_editor_files() {
+    for name in [".index.html.swp", ".index.html.swx", "index.html~", "#index.html#", ".#index.html", "4913", ".DS_Store"] {
+        assert!(is_ignored(&Path::new("/site/web").join(name)), "{name}");
+    }
+
+    for name in ["index.html", "style.scss", "4913.html", "#"] {
+        assert!(!is_ignored(&Path::new("/site/web").join(name)), "{name}");
+    }
+}